@@ -0,0 +1,7 @@
+# Resource file for classes
+#
+# Only messages spelled differently from `en` are here, the others fall back
+# to it.
+
+Paladin = Paladin
+    .desc = Protects the weakness, everyone's favourite knight.
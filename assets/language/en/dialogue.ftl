@@ -0,0 +1,29 @@
+# Resource file for dialogues
+
+# Speakers
+
+speaker-narrator = Narrator
+speaker-stranger = Stranger
+speaker-merchant = Merchant
+
+# test
+
+test-greeting = A stranger waves at you. Hello, { $player }!
+test-ask = Have we met before?
+test-answer-yes = Yes, at the market.
+test-answer-no = I don't think so.
+test-remember = Ah right, you bought { $count ->
+        [one] my last apple
+       *[other] { $count } of my apples
+    }.
+test-farewell = Safe travels.
+
+# merchant
+
+merchant-offer = Fresh apples, only { $price } coins each!
+merchant-buy = I'll take one. (pay { $price } coins)
+merchant-colleague = One merchant to another, how about a gift?
+merchant-threaten = Hand over an apple, or else.
+merchant-leave = No thanks.
+merchant-thanks = Pleasure doing business with you.
+merchant-threatened = Fine, fine! Take it and go away.
@@ -0,0 +1,42 @@
+# Resource file for game data shown by Fluent functions
+
+# Attributes, for STAT()
+
+stat-strength = Strength
+stat-dexterity = Dexterity
+stat-constitution = Constitution
+stat-intelligence = Intelligence
+stat-wisdom = Wisdom
+stat-charisma = Charisma
+
+# Pronouns, for PRONOUN()
+
+pronoun-they = they
+    .object = them
+    .possessive = their
+pronoun-she = she
+    .object = her
+    .possessive = her
+pronoun-he = he
+    .object = him
+    .possessive = his
+
+# Duration units, for DURATION()
+
+duration-hours = h
+duration-minutes = min
+duration-seconds = s
+
+# Character sheet
+
+character-pronouns = Pronouns: { PRONOUN($pronouns) }/{ PRONOUN($pronouns, case: "object") }
+character-stat = { STAT($attribute) }: { $value }
+character-class = Current class: { CLASS($class) }
+
+# Saves
+
+save-play-time = played { DURATION($seconds) }
+
+# Errors
+
+startup-failed = Cannot start the game: { $reason }
@@ -0,0 +1,66 @@
+# Resource file for skills
+
+Endure = Endure
+    .desc = Grit your teeth and take the next blow.
+
+# Citizen
+
+Scrounge = Scrounge
+    .desc = Find something useful where others see only junk.
+
+Backstab = Backstab
+    .desc = Strike an unaware foe where it hurts the most.
+
+Lockpick = Lockpick
+    .desc = No door stays closed for long.
+
+Barter = Barter
+    .desc = Buy cheaper, sell dearer.
+
+Repair = Repair
+    .desc = Mend broken gear and tools.
+
+# Fighter
+
+PowerStrike = Power Strike
+    .desc = A heavy blow that staggers the target.
+
+Rage = Rage
+    .desc = Trade caution for raw strength.
+
+Smite = Smite
+    .desc = A righteous strike against the wicked.
+
+AimedShot = Aimed Shot
+    .desc = Take your time and never miss.
+
+# Scholar
+
+Study = Study
+    .desc = Learn the weakness of what lies before you.
+
+Fireball = Fireball
+    .desc = Burn everything in a small area.
+
+Counterspell = Counterspell
+    .desc = Unravel a spell as it is cast.
+
+Turret = Turret
+    .desc = Deploy a machine that fights on its own.
+
+Transmute = Transmute
+    .desc = Turn one material into another.
+
+# Cleric
+
+Rally = Rally
+    .desc = Give heart to everyone around you.
+
+Heal = Heal
+    .desc = Close wounds through faith.
+
+Inspire = Inspire
+    .desc = A song that makes allies braver.
+
+Command = Command
+    .desc = An order nobody dares to ignore.
@@ -0,0 +1,26 @@
+# Resource file for dialogues
+
+# Speakers
+
+speaker-narrator = 旁白
+speaker-stranger = 陌生人
+speaker-merchant = 商人
+
+# test
+
+test-greeting = 一个陌生人向你挥手. 你好, { $player }!
+test-ask = 我们以前见过吗?
+test-answer-yes = 见过, 在集市上.
+test-answer-no = 应该没有.
+test-remember = 对了, 你买了我 { $count } 个苹果.
+test-farewell = 一路平安.
+
+# merchant
+
+merchant-offer = 新鲜的苹果, 每个只要 { $price } 枚硬币!
+merchant-buy = 我要一个. (支付 { $price } 枚硬币)
+merchant-colleague = 同行之间, 送我一个怎么样?
+merchant-threaten = 把苹果交出来, 不然有你好看.
+merchant-leave = 不用了, 谢谢.
+merchant-thanks = 和你做生意很愉快.
+merchant-threatened = 好好好! 拿去吧, 快走.
@@ -0,0 +1,42 @@
+# Resource file for game data shown by Fluent functions
+
+# Attributes, for STAT()
+
+stat-strength = 力量
+stat-dexterity = 敏捷
+stat-constitution = 体质
+stat-intelligence = 智力
+stat-wisdom = 感知
+stat-charisma = 魅力
+
+# Pronouns, for PRONOUN()
+
+pronoun-they = TA
+    .object = TA
+    .possessive = TA的
+pronoun-she = 她
+    .object = 她
+    .possessive = 她的
+pronoun-he = 他
+    .object = 他
+    .possessive = 他的
+
+# Duration units, for DURATION()
+
+duration-hours = 小时
+duration-minutes = 分钟
+duration-seconds = 秒
+
+# Character sheet
+
+character-pronouns = 代词：{ PRONOUN($pronouns) }
+character-stat = { STAT($attribute) }：{ $value }
+character-class = 当前职业：{ CLASS($class) }
+
+# Saves
+
+save-play-time = 已游玩 { DURATION($seconds) }
+
+# Errors
+
+startup-failed = 无法启动游戏：{ $reason }
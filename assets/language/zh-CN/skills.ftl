@@ -0,0 +1,66 @@
+# Resource file for skills
+
+Endure = 忍耐
+    .desc = 咬紧牙关, 挺过下一击.
+
+# Citizen
+
+Scrounge = 搜罗
+    .desc = 在别人眼中的废物里找到有用的东西.
+
+Backstab = 背刺
+    .desc = 趁敌人不备, 攻其要害.
+
+Lockpick = 开锁
+    .desc = 没有一扇门能关得长久.
+
+Barter = 讨价还价
+    .desc = 买得更便宜, 卖得更贵.
+
+Repair = 修理
+    .desc = 修好损坏的装备和工具.
+
+# Fighter
+
+PowerStrike = 重击
+    .desc = 让目标站立不稳的沉重一击.
+
+Rage = 狂怒
+    .desc = 以谨慎换取蛮力.
+
+Smite = 惩击
+    .desc = 对邪恶之徒的正义一击.
+
+AimedShot = 瞄准射击
+    .desc = 从容瞄准, 箭无虚发.
+
+# Scholar
+
+Study = 钻研
+    .desc = 看穿眼前之物的弱点.
+
+Fireball = 火球术
+    .desc = 焚烧一小片区域内的一切.
+
+Counterspell = 法术反制
+    .desc = 在法术施放时将其瓦解.
+
+Turret = 炮塔
+    .desc = 部署一台自行作战的机器.
+
+Transmute = 嬗变
+    .desc = 将一种物质变为另一种.
+
+# Cleric
+
+Rally = 集结
+    .desc = 鼓舞身边所有人的士气.
+
+Heal = 治疗
+    .desc = 以信仰愈合伤口.
+
+Inspire = 激励
+    .desc = 让同伴更加勇敢的歌声.
+
+Command = 号令
+    .desc = 无人敢违抗的命令.
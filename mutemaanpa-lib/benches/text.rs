@@ -0,0 +1,52 @@
+//! Compare the ways of getting a text: allocating a new string, writing into
+//! a reused buffer, and formatting a message with arguments which cannot be
+//! cached. Passing empty arguments to a message without any formats it on
+//! every call, like before the cache existed.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use fluent_bundle::FluentArgs;
+use mutemaanpa_lib::data::{
+    repository::{messages, text::TextRepository},
+    source::{
+        assets::{Assets, ASSETS_DIR},
+        language::LanguageFileDataSource,
+    },
+};
+
+fn text(c: &mut Criterion) {
+    let assets = Assets::open(ASSETS_DIR, &[]).unwrap();
+    let text = TextRepository::new(LanguageFileDataSource::new(assets)).unwrap();
+    let mut buffer = String::new();
+    let uncached = FluentArgs::new();
+
+    c.bench_function("get_message uncached", |b| {
+        b.iter(|| text.get_attr("Cleric", "desc", Some(&uncached)))
+    });
+    c.bench_function("write_message uncached", |b| {
+        b.iter(|| {
+            buffer.clear();
+            text.write_attr(&mut buffer, "Cleric", "desc", Some(&uncached))
+        })
+    });
+    c.bench_function("get_message", |b| {
+        b.iter(|| text.get_attr("Cleric", "desc", None))
+    });
+    c.bench_function("write_message", |b| {
+        b.iter(|| {
+            buffer.clear();
+            text.write_attr(&mut buffer, "Cleric", "desc", None)
+        })
+    });
+    c.bench_function("format with arguments", |b| {
+        b.iter(|| text.format(messages::test_greeting("Ada")))
+    });
+    c.bench_function("format_into with arguments", |b| {
+        b.iter(|| {
+            buffer.clear();
+            text.format_into(&mut buffer, messages::test_greeting("Ada"))
+        })
+    });
+}
+
+criterion_group!(benches, text);
+criterion_main!(benches);
@@ -0,0 +1,348 @@
+//! Generate the typed message keys of `data::repository::messages` from the
+//! English Fluent files, English being the reference every translation
+//! follows, and embed the English pack into the library for
+//! `data::source::packed`.
+
+use std::{collections::BTreeMap, collections::BTreeSet, env, fmt::Write, fs, path::Path};
+
+use fluent_syntax::{ast, parser};
+
+const ENGLISH_DIR: &str = "../assets/language/en";
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+];
+
+/// Keywords that cannot be raw identifiers.
+const NON_RAW_KEYWORDS: &[&str] = &["self", "Self", "super", "crate"];
+
+/// A message and the variables of its value and of every attribute.
+struct Message {
+    value: Option<BTreeSet<String>>,
+    attributes: BTreeMap<String, BTreeSet<String>>,
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", ENGLISH_DIR);
+    let mut messages = BTreeMap::new();
+    let mut files: Vec<_> = fs::read_dir(ENGLISH_DIR)
+        .expect("the English language pack is missing")
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ftl"))
+        .collect();
+    files.sort();
+    for path in files {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let source = fs::read_to_string(&path).unwrap();
+        let resource = parser::parse(source.as_str()).unwrap_or_else(|(resource, errors)| {
+            for error in errors {
+                println!("cargo:warning={}: {}", path.display(), error);
+            }
+            resource
+        });
+        for entry in resource.body.iter() {
+            if let ast::Entry::Message(message) = entry {
+                messages
+                    .entry(message.id.name.to_string())
+                    .or_insert_with(|| Message {
+                        value: message.value.as_ref().map(variables),
+                        attributes: message
+                            .attributes
+                            .iter()
+                            .map(|a| (a.id.name.to_string(), variables(&a.value)))
+                            .collect(),
+                    });
+            }
+        }
+    }
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).to_path_buf();
+    fs::write(out.join("messages.rs"), generate(&messages)).unwrap();
+    fs::write(out.join("embedded.rs"), embed()).unwrap();
+}
+
+/// The files of the English pack, with their path in the assets.
+fn embed() -> String {
+    let dir = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join(ENGLISH_DIR);
+    let mut files: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_file() && path.extension().is_some())
+        .collect();
+    files.sort();
+    let mut code = String::new();
+    writeln!(code, "/// The English pack, by path in the assets.").unwrap();
+    writeln!(code, "const EMBEDDED_FILES: &[(&str, &[u8])] = &[").unwrap();
+    for path in files {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let name = path.file_name().unwrap().to_str().unwrap();
+        writeln!(
+            code,
+            "    ({:?}, include_bytes!({:?})),",
+            ["language/en/", name].concat(),
+            path.display().to_string()
+        )
+        .unwrap();
+    }
+    writeln!(code, "];").unwrap();
+    code
+}
+
+fn generate(messages: &BTreeMap<String, Message>) -> String {
+    let mut code = String::new();
+    let variants: Vec<(&String, String)> = messages
+        .keys()
+        .map(|id| (id, identifier(&camel_case(id))))
+        .collect();
+    let mut names = BTreeMap::new();
+    for (id, variant) in variants.iter() {
+        claim(&mut names, "variant", id, variant);
+    }
+
+    writeln!(code, "/// Every message of the English language pack.").unwrap();
+    writeln!(code, "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]").unwrap();
+    writeln!(code, "pub enum MessageId {{").unwrap();
+    for (id, variant) in variants.iter() {
+        writeln!(code, "    /// `{}`", id).unwrap();
+        writeln!(code, "    {},", variant).unwrap();
+    }
+    writeln!(code, "}}\n\nimpl MessageId {{").unwrap();
+    writeln!(code, "    pub const ALL: &'static [MessageId] = &[").unwrap();
+    for (_, variant) in variants.iter() {
+        writeln!(code, "        MessageId::{},", variant).unwrap();
+    }
+    writeln!(code, "    ];\n\n    /// The Fluent id of the message.").unwrap();
+    writeln!(
+        code,
+        "    pub fn id(self) -> &'static str {{\n        match self {{"
+    )
+    .unwrap();
+    for (id, variant) in variants.iter() {
+        writeln!(code, "            MessageId::{} => {:?},", variant, id).unwrap();
+    }
+    writeln!(code, "        }}\n    }}\n").unwrap();
+    writeln!(
+        code,
+        "    /// The message of a Fluent id, for ids read from the assets."
+    )
+    .unwrap();
+    writeln!(
+        code,
+        "    pub fn from_id(id: &str) -> Option<MessageId> {{\n        match id {{"
+    )
+    .unwrap();
+    for (id, variant) in variants.iter() {
+        writeln!(
+            code,
+            "            {:?} => Some(MessageId::{}),",
+            id, variant
+        )
+        .unwrap();
+    }
+    writeln!(code, "            _ => None,\n        }}\n    }}\n").unwrap();
+    writeln!(
+        code,
+        "    pub fn attributes(self) -> &'static [&'static str] {{"
+    )
+    .unwrap();
+    writeln!(code, "        match self {{").unwrap();
+    for ((_, variant), message) in variants.iter().zip(messages.values()) {
+        let attributes: Vec<String> = message
+            .attributes
+            .keys()
+            .map(|a| format!("{:?}", a))
+            .collect();
+        writeln!(
+            code,
+            "            MessageId::{} => &[{}],",
+            variant,
+            attributes.join(", ")
+        )
+        .unwrap();
+    }
+    writeln!(code, "        }}\n    }}\n}}").unwrap();
+
+    let mut names = BTreeMap::new();
+    for ((id, variant), message) in variants.iter().zip(messages.values()) {
+        let name = snake_case(id);
+        if let Some(variables) = &message.value {
+            claim(&mut names, "function", id, &identifier(&name));
+            function(&mut code, id, &name, variant, None, variables);
+        }
+        for (attribute, variables) in message.attributes.iter() {
+            let name = [name.as_str(), &snake_case(attribute)].join("_");
+            let key = format!("{}.{}", id, attribute);
+            claim(&mut names, "function", &key, &identifier(&name));
+            function(&mut code, id, &name, variant, Some(attribute), variables);
+        }
+    }
+    code
+}
+
+/// Record that the Fluent `key` generates the Rust `name`. Two keys with the
+/// same name would not compile, like `foo-desc` and the attribute `foo.desc`.
+fn claim(names: &mut BTreeMap<String, String>, kind: &str, key: &str, name: &str) {
+    if let Some(other) = names.insert(name.to_string(), key.to_string()) {
+        panic!(
+            "`{}` and `{}` both become the {} `{}` of the typed messages, rename one of them",
+            other, key, kind, name
+        );
+    }
+}
+
+/// A function building the [`TypedMessage`] of a message value or attribute,
+/// with a parameter for every variable.
+fn function(
+    code: &mut String,
+    id: &str,
+    name: &str,
+    variant: &str,
+    attribute: Option<&str>,
+    variables: &BTreeSet<String>,
+) {
+    let key = match attribute {
+        Some(attribute) => format!("{}.{}", id, attribute),
+        None => id.to_string(),
+    };
+    writeln!(code, "\n/// `{}`", key).unwrap();
+    let mut names = BTreeMap::new();
+    for v in variables.iter() {
+        let variable = format!("{} ${}", key, v);
+        claim(
+            &mut names,
+            "parameter",
+            &variable,
+            &identifier(&snake_case(v)),
+        );
+    }
+    let parameters: Vec<String> = variables
+        .iter()
+        .map(|v| format!("{}: impl Into<FluentValue<'a>>", identifier(&snake_case(v))))
+        .collect();
+    writeln!(
+        code,
+        "pub fn {}<'a>({}) -> TypedMessage<'a> {{",
+        identifier(name),
+        parameters.join(", ")
+    )
+    .unwrap();
+    match variables.is_empty() {
+        true => writeln!(code, "    let args = None;").unwrap(),
+        false => {
+            writeln!(code, "    let mut args = FluentArgs::new();").unwrap();
+            for v in variables.iter() {
+                writeln!(
+                    code,
+                    "    args.set({:?}, {});",
+                    v,
+                    identifier(&snake_case(v))
+                )
+                .unwrap();
+            }
+            writeln!(code, "    let args = Some(args);").unwrap();
+        }
+    }
+    writeln!(
+        code,
+        "    TypedMessage {{\n        id: MessageId::{},\n        attribute: {:?},\n        args,\n    }}\n}}",
+        variant, attribute
+    )
+    .unwrap();
+}
+
+fn variables(pattern: &ast::Pattern<&str>) -> BTreeSet<String> {
+    let mut variables = BTreeSet::new();
+    pattern_variables(pattern, &mut variables);
+    variables
+}
+
+fn pattern_variables(pattern: &ast::Pattern<&str>, variables: &mut BTreeSet<String>) {
+    for element in pattern.elements.iter() {
+        if let ast::PatternElement::Placeable { expression } = element {
+            expression_variables(expression, variables);
+        }
+    }
+}
+
+fn expression_variables(expression: &ast::Expression<&str>, variables: &mut BTreeSet<String>) {
+    match expression {
+        ast::Expression::Inline(inline) => inline_variables(inline, variables),
+        ast::Expression::Select { selector, variants } => {
+            inline_variables(selector, variables);
+            for variant in variants.iter() {
+                pattern_variables(&variant.value, variables);
+            }
+        }
+    }
+}
+
+fn inline_variables(inline: &ast::InlineExpression<&str>, variables: &mut BTreeSet<String>) {
+    let arguments = match inline {
+        ast::InlineExpression::VariableReference { id } => {
+            variables.insert(id.name.to_string());
+            return;
+        }
+        ast::InlineExpression::Placeable { expression } => {
+            expression_variables(expression, variables);
+            return;
+        }
+        ast::InlineExpression::FunctionReference { arguments, .. } => arguments,
+        ast::InlineExpression::TermReference {
+            arguments: Some(arguments),
+            ..
+        } => arguments,
+        _ => return,
+    };
+    for argument in arguments.positional.iter() {
+        inline_variables(argument, variables);
+    }
+    for argument in arguments.named.iter() {
+        inline_variables(&argument.value, variables);
+    }
+}
+
+/// `test-greeting` and `AimedShot` become `test_greeting` and `aimed_shot`.
+fn snake_case(id: &str) -> String {
+    let mut name = String::new();
+    let mut previous: Option<char> = None;
+    for c in id.chars() {
+        match c {
+            '-' => name.push('_'),
+            c if c.is_ascii_uppercase() => {
+                if previous.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit()) {
+                    name.push('_');
+                }
+                name.push(c.to_ascii_lowercase());
+            }
+            c => name.push(c),
+        }
+        previous = Some(c);
+    }
+    name
+}
+
+/// `test-greeting` and `AimedShot` become `TestGreeting` and `AimedShot`.
+fn camel_case(id: &str) -> String {
+    id.split(['-', '_'])
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// `type` becomes `r#type`, and `self` becomes `self_` as it cannot be a raw
+/// identifier.
+fn identifier(name: &str) -> String {
+    if NON_RAW_KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else if KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
+}
@@ -0,0 +1,22 @@
+//! Pack an asset folder into the archive release builds ship:
+//!
+//! ```bash
+//! cargo run --bin pack-assets -- assets assets.pack
+//! ```
+
+use mutemaanpa_lib::data::source::packed;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [assets, output] = args.as_slice() else {
+        eprintln!("usage: pack-assets <assets folder> <archive>");
+        std::process::exit(2);
+    };
+    match packed::pack(assets, output) {
+        Ok(files) => println!("packed {} files into {}", files.len(), output),
+        Err(err) => {
+            eprintln!("cannot pack {} into {}: {}", assets, output, err);
+            std::process::exit(1);
+        }
+    }
+}
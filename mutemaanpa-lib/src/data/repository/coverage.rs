@@ -0,0 +1,207 @@
+//!
+//! # translation coverage
+//!
+//! Compares a language pack against English, the language every text is
+//! written in first, so translators can see what is missing or stale.
+//!
+use std::{collections::BTreeSet, fmt};
+
+use unic_langid::LanguageIdentifier;
+
+use crate::data::source::language::{LanguagePack, MessageShape};
+
+/// [`CoverageReport`] lists what a language pack does not translate like English.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageReport {
+    pub locale: LanguageIdentifier,
+    pub problems: Vec<CoverageProblem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoverageProblem {
+    MissingMessage(String),
+    /// The message is translated but only has attributes.
+    MissingValue(String),
+    MissingAttribute {
+        message: String,
+        attribute: String,
+    },
+    /// A message English does not have, probably renamed or removed since.
+    ExtraMessage(String),
+    ExtraAttribute {
+        message: String,
+        attribute: String,
+    },
+    /// The translation does not use the variables English does, `attribute`
+    /// is none for the value of the message.
+    MismatchedVariables {
+        message: String,
+        attribute: Option<String>,
+        expected: BTreeSet<String>,
+        found: BTreeSet<String>,
+    },
+}
+
+impl CoverageReport {
+    /// Compare `pack` against `reference`, the English pack.
+    pub fn new(reference: &LanguagePack, pack: &LanguagePack) -> CoverageReport {
+        let mut problems = vec![];
+        for (id, expected) in reference.messages.iter() {
+            match pack.messages.get(id) {
+                Some(found) => compare_message(id, expected, found, &mut problems),
+                None => problems.push(CoverageProblem::MissingMessage(id.clone())),
+            }
+        }
+        for id in pack.messages.keys() {
+            if !reference.messages.contains_key(id) {
+                problems.push(CoverageProblem::ExtraMessage(id.clone()));
+            }
+        }
+        CoverageReport {
+            locale: pack.locale.clone(),
+            problems,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+fn compare_message(
+    id: &str,
+    expected: &MessageShape,
+    found: &MessageShape,
+    problems: &mut Vec<CoverageProblem>,
+) {
+    let mismatched =
+        |attribute: Option<&str>, expected: &BTreeSet<String>, found: &BTreeSet<String>| {
+            (expected != found).then(|| CoverageProblem::MismatchedVariables {
+                message: id.to_string(),
+                attribute: attribute.map(str::to_string),
+                expected: expected.clone(),
+                found: found.clone(),
+            })
+        };
+    match (&expected.value, &found.value) {
+        (Some(_), None) => problems.push(CoverageProblem::MissingValue(id.to_string())),
+        (Some(expected), Some(found)) => problems.extend(mismatched(None, expected, found)),
+        _ => {}
+    }
+    for (attribute, expected) in expected.attributes.iter() {
+        match found.attributes.get(attribute) {
+            Some(found) => problems.extend(mismatched(Some(attribute), expected, found)),
+            None => problems.push(CoverageProblem::MissingAttribute {
+                message: id.to_string(),
+                attribute: attribute.clone(),
+            }),
+        }
+    }
+    for attribute in found.attributes.keys() {
+        if !expected.attributes.contains_key(attribute) {
+            problems.push(CoverageProblem::ExtraAttribute {
+                message: id.to_string(),
+                attribute: attribute.clone(),
+            });
+        }
+    }
+}
+
+impl fmt::Display for CoverageProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let variables = |variables: &BTreeSet<String>| {
+            let variables: Vec<String> = variables.iter().map(|v| format!("${}", v)).collect();
+            match variables.is_empty() {
+                true => "none".to_string(),
+                false => variables.join(", "),
+            }
+        };
+        match self {
+            Self::MissingMessage(id) => write!(f, "missing message {}", id),
+            Self::MissingValue(id) => write!(f, "missing value of {}", id),
+            Self::MissingAttribute { message, attribute } => {
+                write!(f, "missing attribute {}.{}", message, attribute)
+            }
+            Self::ExtraMessage(id) => write!(f, "extra message {}", id),
+            Self::ExtraAttribute { message, attribute } => {
+                write!(f, "extra attribute {}.{}", message, attribute)
+            }
+            Self::MismatchedVariables {
+                message,
+                attribute,
+                expected,
+                found,
+            } => {
+                let key = match attribute {
+                    Some(attribute) => format!("{}.{}", message, attribute),
+                    None => message.clone(),
+                };
+                write!(
+                    f,
+                    "{} uses {} instead of {}",
+                    key,
+                    variables(found),
+                    variables(expected)
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+fn shape(value: Option<&[&str]>, attributes: &[(&str, &[&str])]) -> MessageShape {
+    let variables = |v: &[&str]| v.iter().map(|v| v.to_string()).collect();
+    MessageShape {
+        value: value.map(variables),
+        attributes: attributes
+            .iter()
+            .map(|(attribute, v)| (attribute.to_string(), variables(v)))
+            .collect(),
+    }
+}
+
+#[test]
+fn test_compare_message() {
+    let mut problems = vec![];
+    compare_message(
+        "greeting",
+        &shape(Some(&["name"]), &[("desc", &[]), ("title", &["rank"])]),
+        &shape(Some(&["who"]), &[("desc", &[]), ("hint", &[])]),
+        &mut problems,
+    );
+    assert_eq!(
+        problems,
+        vec![
+            CoverageProblem::MismatchedVariables {
+                message: "greeting".to_string(),
+                attribute: None,
+                expected: BTreeSet::from(["name".to_string()]),
+                found: BTreeSet::from(["who".to_string()]),
+            },
+            CoverageProblem::MissingAttribute {
+                message: "greeting".to_string(),
+                attribute: "title".to_string(),
+            },
+            CoverageProblem::ExtraAttribute {
+                message: "greeting".to_string(),
+                attribute: "hint".to_string(),
+            },
+        ]
+    );
+    assert_eq!(
+        problems[0].to_string(),
+        "greeting uses $who instead of $name"
+    );
+
+    problems.clear();
+    compare_message(
+        "Base",
+        &shape(Some(&[]), &[]),
+        &shape(None, &[]),
+        &mut problems,
+    );
+    assert_eq!(
+        problems,
+        vec![CoverageProblem::MissingValue("Base".to_string())]
+    );
+}
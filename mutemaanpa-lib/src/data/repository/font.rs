@@ -0,0 +1,168 @@
+//!
+//! # font repository
+//!
+//! Font module. It finds the font files to show texts with, for every
+//! [`FontRole`] of the language texts are shown in.
+//!
+//! Fonts are looked up like texts, through the language chain of
+//! [`TextRepository`]: the fonts of `zh-CN` first, then the English ones.
+//! Every language contributes its font for the role, its regular font when it
+//! has none, and its fallback fonts. Missing or unreadable files are skipped
+//! and reported.
+//!
+use std::{collections::HashMap, fmt};
+
+use tracing::warn;
+
+use crate::data::{
+    repository::text::TextRepository,
+    source::{
+        assets::AssetHandle,
+        font::{FontFile, FontFileDataSource, FontRole},
+    },
+};
+
+/// [`FontRepository`] is the main interface of this module.
+pub struct FontRepository {
+    source: FontFileDataSource,
+    fonts: HashMap<FontRole, Vec<ResolvedFont>>,
+    problems: Vec<FontProblem>,
+}
+
+/// [`ResolvedFont`] is a font file found in the assets.
+#[derive(Clone)]
+pub struct ResolvedFont {
+    /// The location in the language manifest, like `NotoSans-Regular.ttf`.
+    pub location: String,
+    pub file: AssetHandle<FontFile>,
+}
+
+impl ResolvedFont {
+    /// Path of the file relative to the assets folder.
+    pub fn path(&self) -> String {
+        ["fonts/", &self.location].concat()
+    }
+}
+
+/// [`FontProblem`] is a font of a language manifest that cannot be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontProblem {
+    pub location: String,
+    pub reason: String,
+}
+
+impl fmt::Display for FontProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "font {} skipped: {}", self.location, self.reason)
+    }
+}
+
+impl FontRepository {
+    pub fn new(source: FontFileDataSource, text: &TextRepository) -> FontRepository {
+        let mut font_repo = FontRepository {
+            source,
+            fonts: HashMap::new(),
+            problems: vec![],
+        };
+        font_repo.resolve(text);
+        font_repo
+    }
+
+    /// Find the fonts of the language chain of `text` again, after the
+    /// language changed.
+    pub fn resolve(&mut self, text: &TextRepository) {
+        let mut fonts = HashMap::new();
+        let mut problems: Vec<FontProblem> = vec![];
+        for role in FontRole::ALL {
+            let mut resolved = vec![];
+            for font in text.fonts(role) {
+                match self.source.get_font(&font.location) {
+                    Ok(file) => resolved.push(ResolvedFont {
+                        location: font.location,
+                        file,
+                    }),
+                    Err(err) => {
+                        if problems.iter().all(|p| p.location != font.location) {
+                            warn!("font {} skipped: {}", font.location, err);
+                            problems.push(FontProblem {
+                                location: font.location,
+                                reason: err.to_string(),
+                            });
+                        }
+                    }
+                }
+            }
+            fonts.insert(role, resolved);
+        }
+        self.fonts = fonts;
+        self.problems = problems;
+    }
+
+    /// Fonts of `role`, the primary font first and then its fallbacks.
+    pub fn fonts(&self, role: FontRole) -> &[ResolvedFont] {
+        self.fonts.get(&role).map_or(&[], |fonts| fonts.as_slice())
+    }
+
+    /// The first font of `role` with a glyph for every letter of `text`, or
+    /// the primary font if none has.
+    pub fn font_for(&self, role: FontRole, text: &str) -> Option<&ResolvedFont> {
+        let fonts = self.fonts(role);
+        fonts
+            .iter()
+            .find(|font| {
+                let file = font.file.read();
+                text.chars()
+                    .filter(|c| !c.is_whitespace())
+                    .all(|c| file.covers(c))
+            })
+            .or(fonts.first())
+    }
+
+    /// Fonts of the language manifests that cannot be used.
+    pub fn problems(&self) -> &[FontProblem] {
+        &self.problems
+    }
+}
+
+#[test]
+fn test_resolve_fonts() {
+    use crate::data::source::language::LanguageFileDataSource;
+    crate::tests_utils::logging_init();
+    let mut text =
+        TextRepository::new(LanguageFileDataSource::new(crate::tests_utils::assets())).unwrap();
+    let mut fonts =
+        FontRepository::new(FontFileDataSource::new(crate::tests_utils::assets()), &text);
+    assert!(fonts.problems().is_empty());
+    // English has no bold font, its regular one is used.
+    let bold = fonts.fonts(FontRole::Bold);
+    assert_eq!(bold.len(), 1);
+    assert_eq!(bold[0].path(), "fonts/NotoSans-Regular.ttf");
+
+    // The Simplified Chinese font is not shipped, it is reported until it is.
+    text.change_language("zh-CN").unwrap();
+    fonts.resolve(&text);
+    assert_eq!(fonts.problems().len(), 1);
+    assert_eq!(fonts.problems()[0].location, "NotoSansSC-Regular.ttf");
+    let regular = fonts.font_for(FontRole::Regular, "Ada").unwrap();
+    assert_eq!(regular.location, "NotoSans-Regular.ttf");
+}
+
+#[test]
+fn test_missing_font() {
+    use crate::data::source::{assets::Assets, language::LanguageFileDataSource};
+    crate::tests_utils::logging_init();
+    let root = tempfile::tempdir().unwrap();
+    let german = root.path().join("language").join("de");
+    std::fs::create_dir_all(&german).unwrap();
+    std::fs::write(
+        german.join("manifest.yaml"),
+        "{language: de, fonts: {regular: {location: Missing.ttf}}}",
+    )
+    .unwrap();
+    std::fs::write(german.join("game.ftl"), "hello = Hallo").unwrap();
+    let assets = Assets::open(root.path(), &[]).unwrap();
+    let mut text = TextRepository::new(LanguageFileDataSource::new(assets.clone())).unwrap();
+    text.change_language("de").unwrap();
+    let fonts = FontRepository::new(FontFileDataSource::new(assets), &text);
+    assert!(fonts.problems().iter().any(|p| p.location == "Missing.ttf"));
+}
@@ -0,0 +1,44 @@
+//!
+//! # typed messages
+//!
+//! Message keys generated at build time from the English language pack. Every
+//! message value and attribute has a function taking the variables it uses,
+//! so a misspelled key or a missing argument does not compile:
+//!
+//! ```rust
+//! use mutemaanpa_lib::data::repository::{messages, text::TextRepository};
+//! use mutemaanpa_lib::data::source::{assets::*, language::LanguageFileDataSource};
+//! let assets = Assets::open(ASSETS_DIR, &[]).unwrap();
+//! let text = TextRepository::new(LanguageFileDataSource::new(assets)).unwrap();
+//! let greeting = text.format(messages::test_greeting("Ada"));
+//! assert!(greeting.contains("Ada"));
+//! assert_eq!(text.format(messages::cleric_desc()).is_empty(), false);
+//! ```
+//!
+//! Keys read from the assets, like class names, are checked with
+//! [`MessageId::from_id`] instead.
+//!
+use fluent_bundle::{FluentArgs, FluentValue};
+
+/// [`TypedMessage`] is a message value or attribute with its arguments, ready
+/// for [`TextRepository::format`](super::text::TextRepository::format).
+pub struct TypedMessage<'a> {
+    pub id: MessageId,
+    pub attribute: Option<&'static str>,
+    pub args: Option<FluentArgs<'a>>,
+}
+
+include!(concat!(env!("OUT_DIR"), "/messages.rs"));
+
+#[test]
+fn test_typed_messages() {
+    assert_eq!(MessageId::from_id("Cleric"), Some(MessageId::Cleric));
+    assert_eq!(MessageId::from_id("Clerc"), None);
+    assert_eq!(MessageId::TestGreeting.id(), "test-greeting");
+    assert_eq!(MessageId::Paladin.attributes(), &["desc"]);
+
+    let remember = test_remember(2);
+    assert_eq!(remember.id, MessageId::TestRemember);
+    assert!(remember.args.unwrap().get("count").is_some());
+    assert_eq!(aimed_shot_desc().attribute, Some("desc"));
+}
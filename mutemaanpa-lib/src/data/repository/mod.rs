@@ -1 +1,4 @@
+pub mod coverage;
+pub mod font;
+pub mod messages;
 pub mod text;
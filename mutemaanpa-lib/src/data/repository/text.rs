@@ -4,26 +4,46 @@
 //! Text module. It provides the game all user visible texts. Uses Fluent for translations.
 //! It will always load english language pack.
 //!
-use std::{collections::HashMap, str::FromStr};
+//! Texts are looked up through a chain of languages, negotiated from the
+//! languages asked for and the installed packs: `zh-TW` may be served by
+//! `zh-CN`, and anything still missing by English. Every message is looked up
+//! on its own, so a partial translation shows what it has.
+//!
+use std::{collections::HashMap, str::FromStr, sync::Mutex};
 
-use assets_manager::{Handle, ReloadWatcher};
 use fluent_bundle::FluentArgs;
+use fluent_langneg::{negotiate_languages, NegotiationStrategy};
 use once_cell::sync::Lazy;
-use tracing::warn;
+use tracing::{debug, info, warn};
 use unic_langid::LanguageIdentifier;
 
-use crate::data::source::language::{LanguageFileDataSource, LanguagePack};
+use crate::{
+    data::{
+        repository::{coverage::CoverageReport, messages::TypedMessage},
+        source::{
+            assets::AssetHandle,
+            font::FontRole,
+            language::{
+                Font, FtlDiagnostic, LanguageFileDataSource, LanguageInfo, LanguagePack,
+                TextDirection,
+            },
+            pseudo::PSEUDO_LOCALE,
+        },
+    },
+    Error, Result,
+};
 
 /// [`TextRepository`] is the main interface of this module.
 pub struct TextRepository {
     source: LanguageFileDataSource,
     handles: I18nHandles,
-    watchers: I18nWatchers,
-    first_language: LanguageIdentifier,
+    /// Languages to look texts up in, the best match first and English last.
+    chain: Vec<LanguageIdentifier>,
+    isolation: Isolation,
+    cache: Mutex<MessageCache>,
 }
 
-type I18nHandles = HashMap<LanguageIdentifier, Handle<'static, LanguagePack>>;
-type I18nWatchers = HashMap<LanguageIdentifier, ReloadWatcher<'static>>;
+type I18nHandles = HashMap<LanguageIdentifier, AssetHandle<LanguagePack>>;
 
 static ENGLISH: Lazy<LanguageIdentifier> =
     Lazy::new(|| LanguageIdentifier::from_str("en").unwrap());
@@ -35,93 +55,627 @@ impl TextRepository {
     ///
     /// examples:
     /// ```rust
+    /// use mutemaanpa_lib::data::source::assets::{Assets, ASSETS_DIR};
     /// use mutemaanpa_lib::data::source::language::LanguageFileDataSource;
     /// use mutemaanpa_lib::data::repository::text::TextRepository;
-    /// let source = LanguageFileDataSource::new();
-    /// let text_repo = TextRepository::new(source);
+    /// let assets = Assets::open(ASSETS_DIR, &[]).unwrap();
+    /// let source = LanguageFileDataSource::new(assets);
+    /// let text_repo = TextRepository::new(source).unwrap();
     /// ```
-    pub fn new(source: LanguageFileDataSource) -> TextRepository {
+    pub fn new(source: LanguageFileDataSource) -> Result<TextRepository> {
         let mut text_repo = TextRepository {
             source,
             handles: HashMap::new(),
-            watchers: HashMap::new(),
-            first_language: ENGLISH.clone(),
+            chain: vec![],
+            isolation: Isolation::Always,
+            cache: Mutex::default(),
+        };
+        text_repo.set_languages(std::slice::from_ref(&*ENGLISH))?;
+        Ok(text_repo)
+    }
+
+    /// Negotiate the language chain from `requested`, the preferred language
+    /// first, and load its packs. On failure the current chain is kept.
+    ///
+    /// Fails with [`Error::MissingLanguagePack`] when no installed pack
+    /// matches the preferred language.
+    pub fn set_languages(&mut self, requested: &[LanguageIdentifier]) -> Result<()> {
+        let installed = self.source.installed_languages();
+        let preferred = requested.first().unwrap_or(&ENGLISH);
+        // The pseudo-locale is only used when asked for by name, it would
+        // match any English otherwise.
+        let negotiate = |lang: &LanguageIdentifier| -> Vec<LanguageIdentifier> {
+            if *lang == PSEUDO_LOCALE {
+                return vec![PSEUDO_LOCALE];
+            }
+            negotiate_languages(
+                std::slice::from_ref(lang),
+                &installed,
+                None,
+                NegotiationStrategy::Filtering,
+            )
+            .into_iter()
+            .cloned()
+            .collect()
         };
-        text_repo.load(ENGLISH.clone());
-        text_repo
+        if negotiate(preferred).is_empty() {
+            return Err(Error::MissingLanguagePack(preferred.clone()));
+        }
+        let mut chain = vec![];
+        for lang in requested.iter().flat_map(negotiate) {
+            if !chain.contains(&lang) {
+                chain.push(lang);
+            }
+        }
+        if !chain.contains(&ENGLISH) {
+            chain.push(ENGLISH.clone());
+        }
+        let mut handles = vec![];
+        for lang in chain.iter() {
+            handles.push((lang.clone(), self.source.get_language_pack(lang.clone())?));
+        }
+        for (lang, handle) in handles {
+            self.handles.insert(lang, handle);
+        }
+        let names: Vec<String> = chain.iter().map(|l| l.to_string()).collect();
+        info!("language chain: {}", names.join(" > "));
+        self.chain = chain;
+        self.clear_cache();
+        Ok(())
+    }
+
+    /// Switch to the language tagged `tag`, like `zh-CN`. The current language
+    /// is kept if the tag is invalid or no language pack exists for it.
+    pub fn change_language(&mut self, tag: &str) -> Result<LanguageIdentifier> {
+        self.change_languages(&[tag])
+    }
+
+    /// Like [`TextRepository::change_language`], with more languages to fall
+    /// back to before English. Returns the language texts are now shown in.
+    pub fn change_languages<S: AsRef<str>>(&mut self, tags: &[S]) -> Result<LanguageIdentifier> {
+        let requested = tags
+            .iter()
+            .map(|tag| {
+                let tag = tag.as_ref();
+                tag.parse().map_err(|source| Error::InvalidLanguage {
+                    tag: tag.to_string(),
+                    source,
+                })
+            })
+            .collect::<Result<Vec<LanguageIdentifier>>>()?;
+        self.set_languages(&requested)?;
+        Ok(self.language().clone())
+    }
+
+    /// Installed languages, see [`LanguageFileDataSource::available_languages`].
+    pub fn available_languages(&self) -> Vec<LanguageInfo> {
+        self.source.available_languages()
+    }
+
+    /// Fonts of `role` for the language chain, in the order they should be
+    /// tried. See [`FontRepository`](super::font::FontRepository).
+    pub fn fonts(&self, role: FontRole) -> Vec<Font> {
+        let mut fonts: Vec<Font> = vec![];
+        for handle in self.chain.iter().filter_map(|lang| self.handles.get(lang)) {
+            let pack = handle.read();
+            let font = pack
+                .fonts
+                .get(&role)
+                .or_else(|| pack.fonts.get(&FontRole::Regular));
+            for font in font.into_iter().chain(pack.fallback_fonts.iter()) {
+                if !fonts.contains(font) {
+                    fonts.push(font.clone());
+                }
+            }
+        }
+        fonts
+    }
+
+    /// Problems found in the Fluent files of the language chain, see
+    /// [`LanguagePack::diagnostics`].
+    pub fn diagnostics(&self) -> Vec<FtlDiagnostic> {
+        self.chain
+            .iter()
+            .filter_map(|lang| self.handles.get(lang))
+            .flat_map(|handle| handle.read().diagnostics.clone())
+            .collect()
+    }
+
+    /// Compare every installed language pack against English.
+    ///
+    /// Fails if a pack cannot be loaded.
+    pub fn coverage_reports(&self) -> Result<Vec<CoverageReport>> {
+        let reference = self.source.get_language_pack(ENGLISH.clone())?;
+        let reference = reference.read();
+        let mut reports = vec![];
+        for lang in self.source.installed_languages() {
+            if lang == *ENGLISH {
+                continue;
+            }
+            let pack = self.source.get_language_pack(lang)?;
+            reports.push(CoverageReport::new(&reference, &pack.read()));
+        }
+        Ok(reports)
+    }
+
+    /// The language texts are shown in.
+    pub fn language(&self) -> &LanguageIdentifier {
+        &self.chain[0]
     }
 
-    pub fn load(&mut self, lang: LanguageIdentifier) {
-        let handle = self.source.get_language_pack(lang.clone());
-        let watcher = handle.reload_watcher();
-        self.handles.insert(lang.clone(), handle);
-        self.watchers.insert(lang.clone(), watcher);
-        self.first_language = lang.clone();
+    /// Direction of the language texts are shown in, the front-ends lay
+    /// their UI out with it.
+    pub fn direction(&self) -> TextDirection {
+        TextDirection::of(self.language())
+    }
+
+    pub fn set_isolation(&mut self, isolation: Isolation) {
+        self.isolation = isolation;
+        self.clear_cache();
+    }
+
+    fn clear_cache(&mut self) {
+        let handles = self.chain.iter().filter_map(|lang| self.handles.get(lang));
+        *self.cache.get_mut().unwrap_or_else(|err| err.into_inner()) = MessageCache::new(handles);
+    }
+
+    /// Whether a pack of the language chain was reloaded since the last call,
+    /// every text has to be fetched again then.
+    pub fn reloaded(&mut self) -> bool {
+        let chain = &self.chain;
+        let reloaded = self
+            .handles
+            .iter_mut()
+            .filter(|(lang, _)| chain.contains(lang))
+            .map(|(_, handle)| handle.reloaded())
+            .filter(|reloaded| *reloaded)
+            .count();
+        reloaded > 0
+    }
+
+    /// Languages texts are looked up in, in order.
+    pub fn language_chain(&self) -> &[LanguageIdentifier] {
+        &self.chain
     }
 
     /// This functions checks whether the language pack has updated since the last call
     /// to itself.
     /// Well such features may be better implemented using coroutines but for now just it.
     pub fn has_reloaded(&mut self, lang: LanguageIdentifier) -> bool {
-        self.watchers
+        self.handles
             .get_mut(&lang)
-            .map(|x| x.reloaded())
+            .map(|handle| handle.reloaded())
             .unwrap_or(true)
     }
 
+    /// Format a message checked at build time, see [`messages`](super::messages).
+    pub fn format(&self, message: TypedMessage) -> String {
+        let key = message.id.id();
+        match message.attribute {
+            Some(attr) => self.get_attr(key, attr, message.args.as_ref()),
+            None => self.get_message(key, message.args.as_ref()),
+        }
+    }
+
+    /// Like [`TextRepository::format`], appending the message to `buffer`.
+    pub fn format_into(&self, buffer: &mut String, message: TypedMessage) -> TextDirection {
+        let key = message.id.id();
+        self.write_text(buffer, key, message.attribute, message.args.as_ref())
+    }
+
     pub fn get_message(&self, key: &str, args: Option<&FluentArgs>) -> String {
-        self.get_message_fallback(key, args)
-            .unwrap_or(Self::MISSING_MSG.to_string())
+        self.get_directed_message(key, args).text
     }
 
-    fn get_message_fallback(&self, key: &str, args: Option<&FluentArgs>) -> Option<String> {
-        let bundle = &self
-            .handles
-            .get(&self.first_language)
-            .unwrap_or(self.handles.get(&ENGLISH).unwrap())
-            .read()
-            .bundle;
-        let pattern = bundle.get_message(key)?;
-        let mut errors = vec![];
-        let message = bundle.format_pattern(pattern.value()?, args, &mut errors);
-        match errors.is_empty() {
-            true => Some(message.into_owned()),
-            false => {
-                warn!("get message {} failed: {:?}", key, errors);
-                None
-            }
-        }
+    /// Like [`TextRepository::get_message`], with the direction of the
+    /// language that served the message.
+    pub fn get_directed_message(&self, key: &str, args: Option<&FluentArgs>) -> DirectedText {
+        let mut text = String::new();
+        let direction = self.write_message(&mut text, key, args);
+        DirectedText { text, direction }
+    }
+
+    /// Like [`TextRepository::get_message`], appending the message to
+    /// `buffer` instead of allocating a string, and returning its direction.
+    ///
+    /// Messages without arguments are only formatted once, then copied from a
+    /// cache until a pack of the language chain is reloaded.
+    pub fn write_message(
+        &self,
+        buffer: &mut String,
+        key: &str,
+        args: Option<&FluentArgs>,
+    ) -> TextDirection {
+        self.write_text(buffer, key, None, args)
     }
 
     pub fn get_attr(&self, key: &str, attr: &str, args: Option<&FluentArgs>) -> String {
-        self.get_attr_fallback(key, attr, args)
-            .unwrap_or(Self::MISSING_MSG.to_string())
+        self.get_directed_attr(key, attr, args).text
     }
 
-    fn get_attr_fallback(
+    /// Like [`TextRepository::get_attr`], with the direction of the language
+    /// that served the attribute.
+    pub fn get_directed_attr(
         &self,
         key: &str,
         attr: &str,
         args: Option<&FluentArgs>,
-    ) -> Option<String> {
-        let bundle = &self
-            .handles
-            .get(&self.first_language)
-            .unwrap_or(self.handles.get(&ENGLISH).unwrap())
-            .read()
-            .bundle;
-        let pattern = bundle.get_message(key)?.get_attribute(attr)?.value();
-        let mut errors = vec![];
-        let message = bundle.format_pattern(pattern, args, &mut errors);
-        match errors.is_empty() {
-            true => Some(message.into_owned()),
-            false => {
+    ) -> DirectedText {
+        let mut text = String::new();
+        let direction = self.write_attr(&mut text, key, attr, args);
+        DirectedText { text, direction }
+    }
+
+    /// Like [`TextRepository::write_message`], for the attribute `attr`.
+    pub fn write_attr(
+        &self,
+        buffer: &mut String,
+        key: &str,
+        attr: &str,
+        args: Option<&FluentArgs>,
+    ) -> TextDirection {
+        self.write_text(buffer, key, Some(attr), args)
+    }
+
+    fn write_text(
+        &self,
+        buffer: &mut String,
+        key: &str,
+        attr: Option<&str>,
+        args: Option<&FluentArgs>,
+    ) -> TextDirection {
+        let mut cache = match args {
+            Some(_) => None,
+            None => Some(self.cache.lock().unwrap_or_else(|err| err.into_inner())),
+        };
+        if let Some(text) = cache.as_mut().and_then(|cache| cache.get(key, attr)) {
+            buffer.push_str(&text.text);
+            return text.direction;
+        }
+        let start = buffer.len();
+        let direction = self.lookup(buffer, key, attr, args).unwrap_or_else(|| {
+            buffer.push_str(Self::MISSING_MSG);
+            TextDirection::LeftToRight
+        });
+        if let Some(cache) = cache.as_mut() {
+            let text = DirectedText {
+                text: buffer[start..].to_string(),
+                direction,
+            };
+            cache.insert(key, attr, text);
+        }
+        direction
+    }
+
+    /// Append `key`, or its attribute `attr`, formatted by the first pack of
+    /// the chain able to.
+    fn lookup(
+        &self,
+        buffer: &mut String,
+        key: &str,
+        attr: Option<&str>,
+        args: Option<&FluentArgs>,
+    ) -> Option<TextDirection> {
+        let start = buffer.len();
+        for (i, lang) in self.chain.iter().enumerate() {
+            let Some(handle) = self.handles.get(lang) else {
+                continue;
+            };
+            let pack = handle.read();
+            let bundle = &pack.bundle;
+            let Some(message) = bundle.get_message(key) else {
+                continue;
+            };
+            let pattern = match attr {
+                Some(attr) => message.get_attribute(attr).map(|a| a.value()),
+                None => message.value(),
+            };
+            let Some(pattern) = pattern else {
+                continue;
+            };
+            let pseudo = *lang == PSEUDO_LOCALE;
+            if pseudo {
+                buffer.push('[');
+            }
+            let mut errors = vec![];
+            // Writing into a string cannot fail.
+            let _ = bundle.write_pattern(buffer, pattern, args, &mut errors);
+            if !errors.is_empty() {
                 warn!(
-                    "get message {} with attribute {} failed: {:?}",
-                    key, attr, errors
+                    "get message {}{} in {} failed: {:?}",
+                    key,
+                    attr.map(|a| [".", a].concat()).unwrap_or_default(),
+                    lang,
+                    errors
                 );
-                None
+                buffer.truncate(start);
+                continue;
+            }
+            if pseudo {
+                buffer.push(']');
+            }
+            if self.isolation == Isolation::WhenBidirectional
+                && pack.direction == TextDirection::LeftToRight
+                && buffer[start..].contains(ISOLATION_MARKS)
+                && !buffer[start..].chars().any(is_right_to_left)
+            {
+                let text = buffer.split_off(start);
+                buffer.extend(text.chars().filter(|c| !ISOLATION_MARKS.contains(c)));
+            }
+            match i {
+                0 => debug!("{} served by {}", key, lang),
+                _ => debug!("{} served by fallback {}", key, lang),
             }
+            return Some(pack.direction);
         }
+        None
     }
 }
+
+/// Texts formatted without arguments, dropped when a pack of the language
+/// chain is reloaded.
+#[derive(Default)]
+struct MessageCache {
+    /// The packs of the language chain, to notice their reloads.
+    packs: Vec<AssetHandle<LanguagePack>>,
+    messages: HashMap<String, CachedMessage>,
+}
+
+#[derive(Default)]
+struct CachedMessage {
+    value: Option<DirectedText>,
+    attributes: HashMap<String, DirectedText>,
+}
+
+impl MessageCache {
+    fn new<'a>(handles: impl Iterator<Item = &'a AssetHandle<LanguagePack>>) -> MessageCache {
+        MessageCache {
+            packs: handles.cloned().collect(),
+            messages: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, key: &str, attr: Option<&str>) -> Option<&DirectedText> {
+        // Every pack has to be asked, to be reset.
+        let reloaded = self
+            .packs
+            .iter_mut()
+            .map(|pack| pack.reloaded())
+            .filter(|r| *r)
+            .count();
+        if reloaded > 0 {
+            debug!("language packs reloaded, message cache cleared");
+            self.messages.clear();
+        }
+        let message = self.messages.get(key)?;
+        match attr {
+            Some(attr) => message.attributes.get(attr),
+            None => message.value.as_ref(),
+        }
+    }
+
+    fn insert(&mut self, key: &str, attr: Option<&str>, text: DirectedText) {
+        let message = self.messages.entry(key.to_string()).or_default();
+        match attr {
+            Some(attr) => {
+                message.attributes.insert(attr.to_string(), text);
+            }
+            None => message.value = Some(text),
+        }
+    }
+}
+
+/// [`DirectedText`] is a formatted text and the direction of the language it
+/// is in. It is not the direction of the UI when the text comes from a
+/// fallback language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectedText {
+    pub text: String,
+    pub direction: TextDirection,
+}
+
+/// [`Isolation`] tells whether the Unicode marks Fluent puts around every
+/// placeable, so a right-to-left name does not reorder the text around it,
+/// are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Isolation {
+    /// For renderers supporting bidirectional text, like Godot.
+    Always,
+    /// Only in texts with right-to-left letters, for renderers that would show
+    /// the marks, like most terminals.
+    WhenBidirectional,
+}
+
+/// First strong isolate, left-to-right and right-to-left isolates, and pop
+/// directional isolate.
+const ISOLATION_MARKS: [char; 4] = ['\u{2066}', '\u{2067}', '\u{2068}', '\u{2069}'];
+
+/// Whether `c` is in a block of right-to-left scripts, like Hebrew or Arabic.
+fn is_right_to_left(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0590}'..='\u{08FF}'
+            | '\u{FB1D}'..='\u{FDFF}'
+            | '\u{FE70}'..='\u{FEFF}'
+            | '\u{10800}'..='\u{10FFF}'
+            | '\u{1E800}'..='\u{1EFFF}'
+    )
+}
+
+#[test]
+fn test_change_language() {
+    crate::tests_utils::logging_init();
+    let mut text =
+        TextRepository::new(LanguageFileDataSource::new(crate::tests_utils::assets())).unwrap();
+    let chinese = text.change_language("zh-CN").unwrap();
+    assert_eq!(text.language(), &chinese);
+    assert_eq!(text.get_message("Cleric", None), "牧师");
+
+    assert!(matches!(
+        text.change_language("not a language"),
+        Err(Error::InvalidLanguage { .. })
+    ));
+    assert!(matches!(
+        text.change_language("fr"),
+        Err(Error::MissingLanguagePack(_))
+    ));
+    assert_eq!(text.language(), &chinese);
+    assert_eq!(text.get_message("Cleric", None), "牧师");
+}
+
+#[test]
+fn test_fallback_chain() {
+    crate::tests_utils::logging_init();
+    let mut text =
+        TextRepository::new(LanguageFileDataSource::new(crate::tests_utils::assets())).unwrap();
+    // British English only overrides a few messages.
+    text.change_language("en-GB").unwrap();
+    assert_eq!(
+        text.language_chain(),
+        &["en-GB".parse().unwrap(), ENGLISH.clone()]
+    );
+    assert!(text.get_attr("Paladin", "desc", None).contains("favourite"));
+    assert_eq!(text.get_message("Cleric", None), "Cleric");
+
+    // No Traditional Chinese pack, the closest is Simplified Chinese.
+    let chinese = text.change_language("zh-TW").unwrap();
+    assert_eq!(chinese, "zh-CN".parse::<LanguageIdentifier>().unwrap());
+    assert_eq!(text.get_message("Cleric", None), "牧师");
+
+    text.change_languages(&["zh-TW", "en-GB"]).unwrap();
+    assert_eq!(
+        text.language_chain(),
+        &[chinese, "en-GB".parse().unwrap(), ENGLISH.clone()]
+    );
+    assert!(matches!(
+        text.change_languages(&["fr", "en-GB"]),
+        Err(Error::MissingLanguagePack(_))
+    ));
+}
+
+#[test]
+fn test_coverage_reports() {
+    use crate::data::repository::coverage::CoverageProblem;
+    crate::tests_utils::logging_init();
+    let text =
+        TextRepository::new(LanguageFileDataSource::new(crate::tests_utils::assets())).unwrap();
+    let reports = text.coverage_reports().unwrap();
+    let report = |tag: &str| {
+        let locale: LanguageIdentifier = tag.parse().unwrap();
+        reports.iter().find(|r| r.locale == locale).unwrap()
+    };
+    assert!(reports.iter().all(|r| r.locale != *ENGLISH));
+    // British English only overrides the messages spelled differently.
+    assert!(report("en-GB")
+        .problems
+        .contains(&CoverageProblem::MissingMessage("Cleric".to_string())));
+    assert!(!report("en-GB")
+        .problems
+        .contains(&CoverageProblem::MissingMessage("Paladin".to_string())));
+    assert!(!report("zh-CN").problems.iter().any(|p| matches!(
+        p,
+        CoverageProblem::MismatchedVariables { .. } | CoverageProblem::ExtraMessage(_)
+    )));
+}
+
+#[test]
+fn test_pseudo_locale() {
+    crate::tests_utils::logging_init();
+    let mut text =
+        TextRepository::new(LanguageFileDataSource::new(crate::tests_utils::assets())).unwrap();
+    text.change_language("en-XA").unwrap();
+    assert_eq!(text.language_chain(), &[PSEUDO_LOCALE, ENGLISH.clone()]);
+    assert_eq!(text.get_message("Cleric", None), "[Çļéŕîç~~~]");
+
+    let mut args = FluentArgs::new();
+    args.set("price", 3);
+    let offer = text.get_message("merchant-offer", Some(&args));
+    assert!(offer.starts_with("[Ƒŕéšĥ åþþļéš"));
+    assert!(offer.contains('3'));
+
+    // English is never served by the pseudo-locale.
+    text.change_language("en").unwrap();
+    assert!(!text.language_chain().contains(&PSEUDO_LOCALE));
+}
+
+#[test]
+fn test_isolation() {
+    crate::tests_utils::logging_init();
+    let mut text =
+        TextRepository::new(LanguageFileDataSource::new(crate::tests_utils::assets())).unwrap();
+    assert_eq!(text.direction(), TextDirection::LeftToRight);
+    let mut args = FluentArgs::new();
+    args.set("player", "Ada");
+    let greeting = text.get_directed_message("test-greeting", Some(&args));
+    assert!(greeting.text.contains("\u{2068}Ada\u{2069}"));
+    assert_eq!(greeting.direction, TextDirection::LeftToRight);
+
+    text.set_isolation(Isolation::WhenBidirectional);
+    assert!(text
+        .get_message("test-greeting", Some(&args))
+        .ends_with("Hello, Ada!"));
+    // A Hebrew name would reorder the text around it.
+    args.set("player", "עדה");
+    assert!(text
+        .get_message("test-greeting", Some(&args))
+        .contains("\u{2068}עדה\u{2069}"));
+}
+
+#[test]
+fn test_functions() {
+    use crate::data::repository::messages;
+    crate::tests_utils::logging_init();
+    let mut text =
+        TextRepository::new(LanguageFileDataSource::new(crate::tests_utils::assets())).unwrap();
+    text.set_isolation(Isolation::WhenBidirectional);
+    text.change_language("zh-CN").unwrap();
+    assert_eq!(
+        text.format(messages::character_class("Cleric")),
+        "当前职业：牧师"
+    );
+    assert_eq!(
+        text.format(messages::save_play_time(90)),
+        "已游玩 1 分钟 30 秒"
+    );
+    // Messages British English does not translate use the English functions.
+    text.change_language("en-GB").unwrap();
+    assert_eq!(
+        text.format(messages::character_class("Cleric")),
+        "Current class: Cleric"
+    );
+    assert_eq!(
+        text.format(messages::character_pronouns("she")),
+        "Pronouns: she/her"
+    );
+}
+
+#[test]
+fn test_write_message() {
+    use crate::data::repository::messages;
+    crate::tests_utils::logging_init();
+    let mut text =
+        TextRepository::new(LanguageFileDataSource::new(crate::tests_utils::assets())).unwrap();
+    let mut buffer = String::from("> ");
+    let direction = text.write_attr(&mut buffer, "Cleric", "desc", None);
+    assert_eq!(direction, TextDirection::LeftToRight);
+    assert_eq!(
+        buffer,
+        ["> ", &text.get_attr("Cleric", "desc", None)].concat()
+    );
+    // The second call is served by the cache.
+    assert_eq!(text.get_message("Cleric", None), "Cleric");
+    assert_eq!(text.get_message("Cleric", None), "Cleric");
+    assert_eq!(text.get_message("Clerc", None), "MISSING");
+
+    buffer.clear();
+    text.format_into(&mut buffer, messages::test_greeting("Ada"));
+    assert!(buffer.contains("\u{2068}Ada\u{2069}"));
+    // Changing the isolation drops the cached texts.
+    text.set_isolation(Isolation::WhenBidirectional);
+    buffer.clear();
+    text.format_into(&mut buffer, messages::test_greeting("Ada"));
+    assert!(buffer.ends_with("Hello, Ada!"));
+
+    text.change_language("zh-CN").unwrap();
+    assert_eq!(text.get_message("Cleric", None), "牧师");
+}
@@ -0,0 +1,276 @@
+//! # Asset folders
+//!
+//! [`Assets`] is a cache over an asset folder found at runtime, or the archive
+//! it was packed into, with the enabled mods of the `mods` folder next to it
+//! on top. Data sources share it through an [`Arc`], and the
+//! [`AssetHandle`]s they return keep it alive. The assets are, in order:
+//!
+//! 1. the path given by the front-end, like the `--assets` flag of the tty,
+//! 2. the [`ASSETS_ENV`] environment variable,
+//! 3. `assets.pack` next to the executable, where releases ship it,
+//! 4. `assets` next to the executable,
+//! 5. [`ASSETS_DIR`] of the source tree, when running from it.
+//!
+//! Godot front-ends map their `res://` paths with [`godot_path`].
+//!
+//! When English is missing from the assets or does not load, it is read from
+//! the pack compiled into the library, so menus and error screens always have
+//! their texts. When the assets cannot be opened at all, [`Assets::embedded`]
+//! has only that pack.
+
+use std::{
+    env, io,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use assets_manager::{source::FileSystem, AssetCache, AssetGuard, Compound, Handle, ReloadId};
+use once_cell::sync::Lazy;
+
+use super::{
+    layered::{LayerConflict, LayerFiles, LayeredSource},
+    mods::ModList,
+    packed::{PackedSource, PACK_EXTENSION},
+};
+use crate::Error;
+
+/// The assets of the source tree.
+pub const ASSETS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../assets");
+
+/// Environment variable overriding the asset folder.
+pub const ASSETS_ENV: &str = "MUTEMAANPA_ASSETS";
+
+/// Prefix of the paths relative to a Godot project.
+pub const GODOT_RESOURCES: &str = "res://";
+
+pub struct Assets {
+    root: PathBuf,
+    cache: AssetCache<LayeredSource>,
+    mods: ModList,
+}
+
+impl Assets {
+    /// Open the asset folder or archive `root`, with the mods of `enabled`,
+    /// by id. The cache is dropped with the last handle to it.
+    pub fn open(root: impl AsRef<Path>, enabled: &[String]) -> crate::Result<Arc<Assets>> {
+        let root = root.as_ref().to_path_buf();
+        let base: LayerFiles = match root.is_file() {
+            true => PackedSource::open(&root).map(LayerFiles::from),
+            false => FileSystem::new(&root).map(LayerFiles::from),
+        }
+        .map_err(|source| Error::AssetRoot {
+            path: root.clone(),
+            source,
+        })?;
+        let mods = ModList::load(Self::mods_dir(&root), enabled);
+        let source = mods.source(base);
+        let cache = AssetCache::with_source(source);
+        Ok(Arc::new(Assets { root, cache, mods }))
+    }
+
+    /// The English pack compiled into the library, for when the assets are
+    /// broken. Its root is empty.
+    pub fn embedded() -> Arc<Assets> {
+        static EMBEDDED: Lazy<Arc<Assets>> = Lazy::new(|| {
+            let source = LayeredSource::new(PackedSource::embedded());
+            Arc::new(Assets {
+                root: PathBuf::new(),
+                cache: AssetCache::with_source(source),
+                mods: ModList::default(),
+            })
+        });
+        EMBEDDED.clone()
+    }
+
+    /// Load the asset `id`, see [`AssetCache::load`].
+    pub fn load<T: Compound>(self: &Arc<Self>, id: &str) -> crate::Result<AssetHandle<T>> {
+        let handle: Handle<T> = self.cache.load(id)?;
+        Ok(AssetHandle {
+            last_reload: handle.last_reload_id(),
+            assets: self.clone(),
+            id: id.to_string(),
+            asset: PhantomData,
+        })
+    }
+
+    /// The folder mods are installed in, next to the assets `root`.
+    pub fn mods_dir(root: &Path) -> PathBuf {
+        root.with_file_name("mods")
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn cache(&self) -> &AssetCache<LayeredSource> {
+        &self.cache
+    }
+
+    /// The installed mods, and the enabled ones in load order.
+    pub fn mods(&self) -> &ModList {
+        &self.mods
+    }
+
+    /// Files provided by more than one enabled mod.
+    pub fn mod_conflicts(&self) -> Vec<LayerConflict> {
+        self.cache.raw_source().conflicts()
+    }
+
+    /// Reload every asset changed on disk since the last call. Repositories
+    /// notice the changes through their [`assets_manager::ReloadWatcher`]s.
+    pub fn hot_reload(&self) {
+        self.cache.hot_reload();
+    }
+}
+
+/// [`AssetHandle`] is a loaded asset of an [`Assets`] cache, keeping the
+/// cache alive.
+pub struct AssetHandle<T> {
+    assets: Arc<Assets>,
+    id: String,
+    /// Reload of the asset last seen by [`AssetHandle::reloaded`].
+    last_reload: ReloadId,
+    asset: PhantomData<fn() -> T>,
+}
+
+impl<T: Compound> AssetHandle<T> {
+    fn handle(&self) -> Handle<'_, T> {
+        self.assets
+            .cache
+            .get_cached(&self.id)
+            .expect("loaded assets stay in the cache")
+    }
+
+    /// Lock the asset for reading, it cannot be reloaded meanwhile.
+    pub fn read(&self) -> AssetGuard<'_, T> {
+        self.handle().read()
+    }
+
+    /// A copy of the asset as it is now.
+    pub fn cloned(&self) -> T
+    where
+        T: Clone,
+    {
+        self.handle().cloned()
+    }
+
+    /// The id the asset was loaded with.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Whether the asset was reloaded since the last call, or since it was
+    /// loaded.
+    pub fn reloaded(&mut self) -> bool {
+        let reload = self.handle().last_reload_id();
+        self.last_reload.update(reload)
+    }
+}
+
+impl<T> Clone for AssetHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            assets: self.assets.clone(),
+            id: self.id.clone(),
+            last_reload: self.last_reload,
+            asset: PhantomData,
+        }
+    }
+}
+
+/// Find the asset folder or archive, `flag` being the one asked for on the
+/// command line. Assets asked for explicitly have to exist.
+pub fn resolve_root(flag: Option<&Path>) -> crate::Result<PathBuf> {
+    let missing = |path: PathBuf| Error::AssetRoot {
+        path,
+        source: io::ErrorKind::NotFound.into(),
+    };
+    let explicit = flag
+        .map(Path::to_path_buf)
+        .or_else(|| env::var_os(ASSETS_ENV).map(PathBuf::from));
+    if let Some(root) = explicit {
+        return match root.exists() {
+            true => Ok(root),
+            false => Err(missing(root)),
+        };
+    }
+    let shipped = env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join("assets")))
+        .unwrap_or_else(|| PathBuf::from("assets"));
+    let packed = shipped.with_extension(PACK_EXTENSION);
+    [packed.clone(), shipped, PathBuf::from(ASSETS_DIR)]
+        .into_iter()
+        .find(|root| root.exists())
+        .ok_or_else(|| missing(packed))
+}
+
+/// Map `res://assets` to `assets` in `project_dir`, the folder of the Godot
+/// project. Other paths are kept.
+pub fn godot_path(path: &str, project_dir: &Path) -> PathBuf {
+    match path.strip_prefix(GODOT_RESOURCES) {
+        Some(relative) => project_dir.join(relative),
+        None => PathBuf::from(path),
+    }
+}
+
+#[test]
+fn test_resolve_root() {
+    let root = resolve_root(Some(Path::new(ASSETS_DIR))).unwrap();
+    assert_eq!(root, Path::new(ASSETS_DIR));
+    assert!(matches!(
+        resolve_root(Some(Path::new("missing-assets"))),
+        Err(Error::AssetRoot { .. })
+    ));
+    // Tests do not run next to shipped assets.
+    if env::var_os(ASSETS_ENV).is_none() {
+        assert_eq!(resolve_root(None).unwrap(), Path::new(ASSETS_DIR));
+    }
+
+    let project = Path::new("/games/mutemaanpa");
+    assert_eq!(
+        godot_path("res://assets", project),
+        Path::new("/games/mutemaanpa/assets")
+    );
+    assert_eq!(godot_path("/opt/assets", project), Path::new("/opt/assets"));
+}
+
+#[test]
+fn test_fixture_assets() {
+    use crate::data::{repository::text::TextRepository, source::language::LanguageFileDataSource};
+    use unic_langid::langid;
+    crate::tests_utils::logging_init();
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("assets");
+    let english = root.join("language").join("en");
+    std::fs::create_dir_all(&english).unwrap();
+    std::fs::write(
+        english.join("manifest.yaml"),
+        "{language: en, name: Fixture, fonts: {}}",
+    )
+    .unwrap();
+    std::fs::write(english.join("fixture.ftl"), "fixture = Fixture").unwrap();
+
+    // Every instance has its own cache.
+    let assets = Assets::open(&root, &[]).unwrap();
+    assert_eq!(assets.root(), root);
+    assert!(assets.mods().installed().is_empty());
+    let text = TextRepository::new(LanguageFileDataSource::new(assets)).unwrap();
+    assert_eq!(text.get_message("fixture", None), "Fixture");
+    let shipped = TextRepository::new(LanguageFileDataSource::new(crate::tests_utils::assets()));
+    assert_eq!(shipped.unwrap().get_message("fixture", None), "MISSING");
+
+    // The embedded pack is not merged into the English of the assets.
+    assert_eq!(text.get_message("Cleric", None), "MISSING");
+    // English missing from the assets is read from the embedded pack.
+    std::fs::remove_dir_all(&english).unwrap();
+    let assets = Assets::open(&root, &[]).unwrap();
+    let text = TextRepository::new(LanguageFileDataSource::new(assets)).unwrap();
+    assert_eq!(text.get_message("Cleric", None), "Cleric");
+    let source = LanguageFileDataSource::new(Assets::open(&root, &[]).unwrap());
+    assert_eq!(source.installed_languages(), vec![langid!("en")]);
+    assert_eq!(source.available_languages()[0].name, "English");
+    let embedded = TextRepository::new(LanguageFileDataSource::new(Assets::embedded()));
+    assert_eq!(embedded.unwrap().get_message("Cleric", None), "Cleric");
+}
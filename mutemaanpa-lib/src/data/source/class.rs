@@ -0,0 +1,95 @@
+//! # Class data sources
+//!
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::assets::{AssetHandle, Assets};
+use crate::gameplay::{
+    class::{ClassIdentifier, ClassRequirements, ClassTree},
+    skill::SkillIdentifier,
+};
+
+/// [`ClassManifest`] is the on-disk form of the class tree. Classes are stored
+/// as a flat list so designers can edit them without nesting, and the tree is
+/// rebuilt (and validated) by [`ClassTree::from_definitions`].
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ClassManifest {
+    pub classes: Vec<ClassDefinition>,
+}
+
+/// [`ClassDefinition`] is a single class in the [`ClassManifest`], refering to
+/// its subclasses by name.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ClassDefinition {
+    pub name: ClassIdentifier,
+    #[serde(default)]
+    pub children: Vec<ClassIdentifier>,
+    #[serde(default)]
+    pub requires: ClassRequirements,
+    #[serde(default)]
+    pub skills: Vec<SkillIdentifier>,
+}
+
+/// Class manifest is loaded by [`assets_manager`].
+impl assets_manager::Asset for ClassManifest {
+    const EXTENSION: &'static str = "yaml";
+    type Loader = assets_manager::loader::YamlLoader;
+}
+
+/// Class trees are dynamic assets built from a [`ClassManifest`] of the same id.
+impl assets_manager::Compound for ClassTree {
+    fn load(
+        cache: assets_manager::AnyCache,
+        id: &assets_manager::SharedString,
+    ) -> Result<Self, assets_manager::BoxedError> {
+        let manifest = cache.load::<ClassManifest>(id)?;
+        let tree = ClassTree::from_definitions(&manifest.read().classes)?;
+        Ok(tree)
+    }
+}
+
+pub struct ClassFileDataSource {
+    assets: Arc<Assets>,
+}
+
+impl ClassFileDataSource {
+    /// Asset id of the class tree shipped with the game.
+    pub const CLASS_TREE: &'static str = "gameplay.classes";
+
+    pub fn new(assets: Arc<Assets>) -> ClassFileDataSource {
+        ClassFileDataSource { assets }
+    }
+
+    pub fn get_class_tree(&self) -> crate::Result<AssetHandle<ClassTree>> {
+        self.assets.load(Self::CLASS_TREE)
+    }
+}
+
+#[test]
+fn test_manifest() {
+    crate::tests_utils::logging_init();
+    let manifest = ClassManifest {
+        classes: vec![
+            ClassDefinition {
+                name: String::from("Base"),
+                children: vec![String::from("Fighter")],
+                requires: ClassRequirements {
+                    cost: 0,
+                    ..Default::default()
+                },
+                skills: vec![String::from("Endure")],
+            },
+            ClassDefinition {
+                name: String::from("Fighter"),
+                children: vec![],
+                requires: ClassRequirements::default(),
+                skills: vec![],
+            },
+        ],
+    };
+    let manifest_str = serde_yaml::to_string(&manifest);
+    let recovered_struct: ClassManifest = serde_yaml::from_str(&manifest_str.unwrap()).unwrap();
+    assert_eq!(manifest, recovered_struct);
+}
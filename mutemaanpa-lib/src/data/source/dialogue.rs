@@ -0,0 +1,50 @@
+//! # Dialogue data sources
+//!
+
+use std::{collections::HashMap, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use super::assets::{AssetHandle, Assets};
+use crate::gameplay::dialogue::{DialogueGraph, DialogueNode, NodeIdentifier};
+
+/// [`DialogueManifest`] is the on-disk form of a dialogue graph, stored under
+/// `dialogue/<id>.yaml`. It is checked by [`DialogueGraph::from_manifest`].
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct DialogueManifest {
+    pub start: NodeIdentifier,
+    pub nodes: HashMap<NodeIdentifier, DialogueNode>,
+}
+
+/// Dialogue manifest is loaded by [`assets_manager`].
+impl assets_manager::Asset for DialogueManifest {
+    const EXTENSION: &'static str = "yaml";
+    type Loader = assets_manager::loader::YamlLoader;
+}
+
+/// Dialogue graphs are dynamic assets built from a [`DialogueManifest`] of the same id.
+impl assets_manager::Compound for DialogueGraph {
+    fn load(
+        cache: assets_manager::AnyCache,
+        id: &assets_manager::SharedString,
+    ) -> Result<Self, assets_manager::BoxedError> {
+        let manifest = cache.load::<DialogueManifest>(id)?.cloned();
+        let graph = DialogueGraph::from_manifest(manifest)?;
+        Ok(graph)
+    }
+}
+
+pub struct DialogueFileDataSource {
+    assets: Arc<Assets>,
+}
+
+impl DialogueFileDataSource {
+    pub fn new(assets: Arc<Assets>) -> DialogueFileDataSource {
+        DialogueFileDataSource { assets }
+    }
+
+    pub fn get_dialogue(&self, dialogue: &str) -> crate::Result<AssetHandle<DialogueGraph>> {
+        let id = ["dialogue.", dialogue].concat();
+        self.assets.load(&id)
+    }
+}
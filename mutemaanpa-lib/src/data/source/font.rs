@@ -0,0 +1,136 @@
+//! # Font data sources
+//!
+//! Font files live in `assets/fonts`. Language manifests refer to them by
+//! file name, like `NotoSans-Regular.ttf`.
+
+use std::{borrow::Cow, sync::Arc};
+
+use assets_manager::{loader::Loader, BoxedError};
+use serde::{Deserialize, Serialize};
+
+use super::assets::{AssetHandle, Assets};
+use crate::Error;
+
+/// [`FontRole`] is what a font is used for. A language pack without a font
+/// for a role uses its regular font.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum FontRole {
+    Regular,
+    Serif,
+    Bold,
+    Monospace,
+}
+
+impl FontRole {
+    pub const ALL: [FontRole; 4] = [
+        FontRole::Regular,
+        FontRole::Serif,
+        FontRole::Bold,
+        FontRole::Monospace,
+    ];
+}
+
+/// [`FontFile`] is a TrueType or OpenType font, checked to be readable when
+/// it is loaded.
+pub struct FontFile {
+    pub data: Vec<u8>,
+    /// Characters with a glyph, as sorted ranges of code points, read once
+    /// when the font is loaded.
+    coverage: Vec<(u32, u32)>,
+}
+
+impl FontFile {
+    /// Whether the font has a glyph for `c`.
+    pub fn covers(&self, c: char) -> bool {
+        let c = c as u32;
+        let i = self.coverage.partition_point(|(_, end)| *end < c);
+        self.coverage.get(i).is_some_and(|(start, _)| *start <= c)
+    }
+}
+
+/// Code points the Unicode character maps of `face` give a glyph.
+fn coverage(face: &ttf_parser::Face) -> Vec<(u32, u32)> {
+    let mut code_points = vec![];
+    let subtables = face.tables().cmap.iter().flat_map(|cmap| cmap.subtables);
+    for subtable in subtables.filter(|s| s.is_unicode()) {
+        subtable.codepoints(|c| {
+            if subtable.glyph_index(c).is_some_and(|glyph| glyph.0 != 0) {
+                code_points.push(c);
+            }
+        });
+    }
+    code_points.sort_unstable();
+    code_points.dedup();
+    let mut ranges: Vec<(u32, u32)> = vec![];
+    for c in code_points {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == c => *end = c,
+            _ => ranges.push((c, c)),
+        }
+    }
+    ranges
+}
+
+pub struct FontLoader;
+
+impl Loader<FontFile> for FontLoader {
+    fn load(content: Cow<[u8]>, _: &str) -> Result<FontFile, BoxedError> {
+        let coverage = coverage(&ttf_parser::Face::parse(&content, 0)?);
+        Ok(FontFile {
+            data: content.into_owned(),
+            coverage,
+        })
+    }
+}
+
+impl assets_manager::Asset for FontFile {
+    const EXTENSIONS: &'static [&'static str] = &["ttf", "otf"];
+    type Loader = FontLoader;
+}
+
+pub struct FontFileDataSource {
+    assets: Arc<Assets>,
+}
+
+impl FontFileDataSource {
+    pub fn new(assets: Arc<Assets>) -> FontFileDataSource {
+        FontFileDataSource { assets }
+    }
+
+    /// Load the font file `location`, a file name in `assets/fonts` with its
+    /// extension.
+    pub fn get_font(&self, location: &str) -> crate::Result<AssetHandle<FontFile>> {
+        let stem = location
+            .strip_suffix(".ttf")
+            .or_else(|| location.strip_suffix(".otf"))
+            .filter(|stem| !stem.is_empty() && !stem.contains(['/', '\\', '.']))
+            .ok_or_else(|| Error::InvalidFontLocation(location.to_string()))?;
+        let id = ["fonts.", stem].concat();
+        self.assets.load(&id)
+    }
+}
+
+#[test]
+fn test_get_font() {
+    crate::tests_utils::logging_init();
+    let source = FontFileDataSource::new(crate::tests_utils::assets());
+    let font = source.get_font("NotoSans-Regular.ttf").unwrap();
+    assert!(font.read().covers('a'));
+    assert!(!font.read().covers('牧'));
+    let file = font.read();
+    let face = ttf_parser::Face::parse(&file.data, 0).unwrap();
+    for c in ('\0'..'\u{3000}').chain(['\u{10FFFF}']) {
+        let glyph = face.glyph_index(c).is_some_and(|g| g.0 != 0);
+        assert_eq!(file.covers(c), glyph, "{:?}", c);
+    }
+
+    assert!(matches!(
+        source.get_font("NotoSans-Regular"),
+        Err(Error::InvalidFontLocation(_))
+    ));
+    assert!(matches!(
+        source.get_font("Missing.ttf"),
+        Err(Error::Asset(_))
+    ));
+}
@@ -0,0 +1,174 @@
+//! # Fluent functions
+//!
+//! Functions translators can call to show game data in their own words:
+//!
+//! - `CLASS($class)`: name of a class, like `Cleric`.
+//! - `STAT($attribute)`: name of an attribute, like `strength`, from the
+//!   message `stat-<attribute>`.
+//! - `PRONOUN($pronouns, case: "object")`: pronoun of a character, from the
+//!   message `pronoun-<pronouns>`. The case is `subject` by default, `object`
+//!   and `possessive` are its attributes.
+//! - `DURATION($seconds)`: a duration in hours, minutes and seconds, with the
+//!   units of the messages `duration-hours`, `duration-minutes` and
+//!   `duration-seconds`.
+//!
+//! Fluent functions cannot format messages, so they read [`PlainTexts`]
+//! formatted when the pack is loaded.
+
+use std::{collections::HashMap, sync::Arc};
+
+use fluent_bundle::{bundle::FluentBundle, FluentArgs, FluentResource, FluentValue};
+use intl_memoizer::concurrent;
+use tracing::warn;
+
+/// Every text of a pack that needs no argument, by `id` or `id.attribute`.
+pub type PlainTexts = HashMap<String, String>;
+
+pub fn add_functions(
+    bundle: &mut FluentBundle<FluentResource, concurrent::IntlLangMemoizer>,
+    texts: Arc<PlainTexts>,
+) {
+    let functions: [(&str, Function); 4] = [
+        ("CLASS", class),
+        ("STAT", stat),
+        ("PRONOUN", pronoun),
+        ("DURATION", duration),
+    ];
+    for (name, function) in functions {
+        let texts = texts.clone();
+        let added = bundle.add_function(name, move |positional, named| {
+            function(&texts, positional, named)
+        });
+        if let Err(err) = added {
+            warn!("cannot add Fluent function {}: {}", name, err);
+        }
+    }
+}
+
+type Function = for<'a> fn(&PlainTexts, &[FluentValue<'a>], &FluentArgs) -> FluentValue<'a>;
+
+/// The text of `key`, or `fallback` if the pack has none.
+fn text<'a>(texts: &PlainTexts, key: &str, fallback: &str) -> FluentValue<'a> {
+    texts
+        .get(key)
+        .cloned()
+        .unwrap_or_else(|| fallback.to_string())
+        .into()
+}
+
+fn string<'a>(value: Option<&'a FluentValue>) -> Option<&'a str> {
+    match value? {
+        FluentValue::String(s) => Some(s),
+        _ => None,
+    }
+}
+
+fn class<'a>(
+    texts: &PlainTexts,
+    positional: &[FluentValue<'a>],
+    _: &FluentArgs,
+) -> FluentValue<'a> {
+    match string(positional.first()) {
+        Some(class) => text(texts, class, class),
+        None => FluentValue::Error,
+    }
+}
+
+fn stat<'a>(texts: &PlainTexts, positional: &[FluentValue<'a>], _: &FluentArgs) -> FluentValue<'a> {
+    match string(positional.first()) {
+        Some(attribute) => text(texts, &["stat-", attribute].concat(), attribute),
+        None => FluentValue::Error,
+    }
+}
+
+fn pronoun<'a>(
+    texts: &PlainTexts,
+    positional: &[FluentValue<'a>],
+    named: &FluentArgs,
+) -> FluentValue<'a> {
+    let Some(pronouns) = string(positional.first()) else {
+        return FluentValue::Error;
+    };
+    let key = match string(named.get("case")) {
+        None | Some("subject") => ["pronoun-", pronouns].concat(),
+        Some(case) => ["pronoun-", pronouns, ".", case].concat(),
+    };
+    text(texts, &key, pronouns)
+}
+
+fn duration<'a>(
+    texts: &PlainTexts,
+    positional: &[FluentValue<'a>],
+    _: &FluentArgs,
+) -> FluentValue<'a> {
+    let Some(FluentValue::Number(seconds)) = positional.first() else {
+        return FluentValue::Error;
+    };
+    let seconds = seconds.value.max(0.0) as u64;
+    let parts = [
+        (seconds / 3600, "duration-hours", "h"),
+        (seconds / 60 % 60, "duration-minutes", "min"),
+        (seconds % 60, "duration-seconds", "s"),
+    ];
+    let mut duration: Vec<String> = parts
+        .iter()
+        .filter(|(count, _, _)| *count > 0)
+        .map(|(count, key, unit)| {
+            let unit = texts.get(*key).map_or(*unit, |unit| unit.as_str());
+            format!("{} {}", count, unit)
+        })
+        .collect();
+    if duration.is_empty() {
+        duration.push(format!(
+            "0 {}",
+            texts.get("duration-seconds").map_or("s", |u| u)
+        ));
+    }
+    duration.join(" ").into()
+}
+
+#[test]
+fn test_functions() {
+    use unic_langid::langid;
+    let source = "\
+hello = { PRONOUN($pronouns) } ({ PRONOUN($pronouns, case: \"object\") }) is a { CLASS($class) }
+stat = { STAT($attribute) }: { $value }
+played = Played for { DURATION($seconds) }
+";
+    let texts = PlainTexts::from([
+        ("Cleric".to_string(), "Cleric".to_string()),
+        ("pronoun-she".to_string(), "she".to_string()),
+        ("pronoun-she.object".to_string(), "her".to_string()),
+        ("stat-strength".to_string(), "Strength".to_string()),
+        ("duration-minutes".to_string(), "min".to_string()),
+    ]);
+    let mut bundle = FluentBundle::new_concurrent(vec![langid!("en")]);
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(FluentResource::try_new(source.to_string()).unwrap())
+        .unwrap();
+    add_functions(&mut bundle, Arc::new(texts));
+    let format = |id: &str, args: &FluentArgs| {
+        let mut errors = vec![];
+        let pattern = bundle.get_message(id).unwrap().value().unwrap();
+        bundle
+            .format_pattern(pattern, Some(args), &mut errors)
+            .into_owned()
+    };
+
+    let mut args = FluentArgs::new();
+    args.set("pronouns", "she");
+    args.set("class", "Cleric");
+    args.set("attribute", "strength");
+    args.set("value", 12);
+    args.set("seconds", 3725);
+    assert_eq!(format("hello", &args), "she (her) is a Cleric");
+    assert_eq!(format("stat", &args), "Strength: 12");
+    assert_eq!(format("played", &args), "Played for 1 h 2 min 5 s");
+
+    // Unknown data is shown as it is.
+    args.set("class", "Necromancer");
+    assert!(format("hello", &args).ends_with("a Necromancer"));
+    args.set("seconds", 0);
+    assert_eq!(format("played", &args), "Played for 0 s");
+}
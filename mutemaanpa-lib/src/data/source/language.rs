@@ -1,16 +1,30 @@
 //! # Language data sources
 //!
 
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    fmt,
+    sync::Arc,
+};
 
-use anyhow::Result;
-use assets_manager::{loader::StringLoader, Handle};
+use assets_manager::{
+    loader::StringLoader,
+    source::{DirEntry, Source},
+};
 use fluent_bundle::{bundle::FluentBundle, FluentResource};
+use fluent_syntax::ast;
 use intl_memoizer::concurrent;
 use serde::{Deserialize, Serialize};
-use unic_langid::LanguageIdentifier;
+use tracing::warn;
+use unic_langid::{langid, CharacterDirection, LanguageIdentifier};
 
-use super::ASSETS;
+use super::{
+    assets::{AssetHandle, Assets},
+    font::FontRole,
+    functions::{self, PlainTexts},
+    pseudo::{self, PSEUDO_LOCALE},
+};
+use crate::Error;
 
 /// [`LanguagePack`] contains three things:
 ///     1. Which language? This is provided by [`LanguageIdentifier`].
@@ -20,8 +34,122 @@ use super::ASSETS;
 /// This is a plain data struct, which is provided by [`LanguageHotFileDataSource`]. It is then
 pub struct LanguagePack {
     pub locale: LanguageIdentifier,
+    pub direction: TextDirection,
     pub bundle: FluentBundle<FluentResource, concurrent::IntlLangMemoizer>,
     pub fonts: Fonts,
+    pub fallback_fonts: Vec<Font>,
+    /// Every message in the bundle, by id.
+    pub messages: BTreeMap<String, MessageShape>,
+    /// Problems found in the Fluent files, the pack loads without the broken entries.
+    pub diagnostics: Vec<FtlDiagnostic>,
+    /// Texts read by the [`functions`] of the bundle.
+    plain_texts: Arc<PlainTexts>,
+}
+
+/// [`TextDirection`] is the direction a language is written in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextDirection {
+    LeftToRight,
+    RightToLeft,
+}
+
+impl TextDirection {
+    /// Direction of `locale`, from its script. Vertical scripts like
+    /// Traditional Mongolian are shown left to right.
+    pub fn of(locale: &LanguageIdentifier) -> TextDirection {
+        match locale.character_direction() {
+            CharacterDirection::RTL => TextDirection::RightToLeft,
+            _ => TextDirection::LeftToRight,
+        }
+    }
+}
+
+/// [`MessageShape`] is what a message is made of, to compare translations
+/// without formatting them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageShape {
+    /// Variables used by the value, none if the message only has attributes.
+    pub value: Option<BTreeSet<String>>,
+    /// Variables used by every attribute.
+    pub attributes: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl MessageShape {
+    fn new(message: &ast::Message<&str>) -> MessageShape {
+        let variables = |pattern: &ast::Pattern<&str>| {
+            let mut variables = BTreeSet::new();
+            pattern_variables(pattern, &mut variables);
+            variables
+        };
+        MessageShape {
+            value: message.value.as_ref().map(variables),
+            attributes: message
+                .attributes
+                .iter()
+                .map(|attribute| (attribute.id.name.to_string(), variables(&attribute.value)))
+                .collect(),
+        }
+    }
+}
+
+fn pattern_variables(pattern: &ast::Pattern<&str>, variables: &mut BTreeSet<String>) {
+    for element in pattern.elements.iter() {
+        if let ast::PatternElement::Placeable { expression } = element {
+            expression_variables(expression, variables);
+        }
+    }
+}
+
+fn expression_variables(expression: &ast::Expression<&str>, variables: &mut BTreeSet<String>) {
+    match expression {
+        ast::Expression::Inline(inline) => inline_variables(inline, variables),
+        ast::Expression::Select { selector, variants } => {
+            inline_variables(selector, variables);
+            for variant in variants.iter() {
+                pattern_variables(&variant.value, variables);
+            }
+        }
+    }
+}
+
+fn inline_variables(inline: &ast::InlineExpression<&str>, variables: &mut BTreeSet<String>) {
+    let arguments = match inline {
+        ast::InlineExpression::VariableReference { id } => {
+            variables.insert(id.name.to_string());
+            return;
+        }
+        ast::InlineExpression::Placeable { expression } => {
+            expression_variables(expression, variables);
+            return;
+        }
+        ast::InlineExpression::FunctionReference { arguments, .. } => arguments,
+        ast::InlineExpression::TermReference {
+            arguments: Some(arguments),
+            ..
+        } => arguments,
+        _ => return,
+    };
+    for argument in arguments.positional.iter() {
+        inline_variables(argument, variables);
+    }
+    for argument in arguments.named.iter() {
+        inline_variables(&argument.value, variables);
+    }
+}
+
+impl LanguagePack {
+    /// Share of the messages of `reference` this pack translates, from 0 to 1.
+    pub fn completeness(&self, reference: &LanguagePack) -> f32 {
+        if reference.messages.is_empty() {
+            return 1.0;
+        }
+        let translated = reference
+            .messages
+            .keys()
+            .filter(|id| self.messages.contains_key(*id))
+            .count();
+        translated as f32 / reference.messages.len() as f32
+    }
 }
 
 /// Language Packs are dynamic assets, so we load them using asset_manager.
@@ -31,24 +159,96 @@ impl assets_manager::Compound for LanguagePack {
         id: &assets_manager::SharedString,
     ) -> Result<Self, assets_manager::BoxedError> {
         let locale: LanguageIdentifier = id.parse()?;
-        let manifest = cache.load::<LanguageManifest>(&["language.", id, ".manifest"].concat())?;
+        // The pseudo-locale is English, transformed.
+        let pseudo = locale == PSEUDO_LOCALE;
+        let dir = ["language.", if pseudo { "en" } else { id }].concat();
+        let manifest = cache.load::<LanguageManifest>(&[&dir, ".manifest"].concat())?;
 
         let mut bundle = FluentBundle::new_concurrent(vec![locale.clone()]);
-        cache
-            .load_dir::<FluentFilePath>(&["language.", id].concat(), true)?
-            .ids()
-            .flat_map(|id| load_ftl(&cache, id))
-            .map(|lang_file| FluentResource::try_new(lang_file).unwrap())
-            .for_each(|lang_res| bundle.add_resource(lang_res).unwrap());
+        if pseudo {
+            bundle.set_transform(Some(pseudo::transform));
+        }
+        let mut messages = BTreeMap::new();
+        let mut definitions = HashMap::new();
+        let mut diagnostics = vec![];
+        for file in cache.load_dir::<FluentFilePath>(&dir, true)?.ids() {
+            let Some(source) = load_ftl(&cache, file) else {
+                continue;
+            };
+            let resource = add_ftl(file, source, &mut definitions, &mut diagnostics);
+            for entry in resource.entries() {
+                if let ast::Entry::Message(message) = entry {
+                    messages
+                        .entry(message.id.name.to_string())
+                        .or_insert_with(|| MessageShape::new(message));
+                }
+            }
+            // Overrides are already diagnosed, the first definition is kept.
+            let _ = bundle.add_resource(resource);
+        }
+        for diagnostic in diagnostics.iter() {
+            warn!("{}", diagnostic);
+        }
+        // Functions read English texts the pack does not translate.
+        let mut texts = match dir == "language.en" {
+            true => PlainTexts::new(),
+            false => cache
+                .load::<LanguagePack>("en")
+                .map(|english| (*english.read().plain_texts).clone())
+                .unwrap_or_default(),
+        };
+        texts.extend(plain_texts(&bundle, &messages));
+        let plain_texts = Arc::new(texts);
+        functions::add_functions(&mut bundle, plain_texts.clone());
 
         Ok(Self {
             bundle,
+            direction: TextDirection::of(&locale),
             locale,
             fonts: manifest.read().fonts.clone(),
+            fallback_fonts: manifest.read().fallback_fonts.clone(),
+            messages,
+            diagnostics,
+            plain_texts,
         })
     }
 }
 
+/// Format every message value and attribute that uses no variable.
+fn plain_texts(
+    bundle: &FluentBundle<FluentResource, concurrent::IntlLangMemoizer>,
+    messages: &BTreeMap<String, MessageShape>,
+) -> PlainTexts {
+    let mut texts = PlainTexts::new();
+    for (id, shape) in messages.iter() {
+        let Some(message) = bundle.get_message(id) else {
+            continue;
+        };
+        let value = shape
+            .value
+            .as_ref()
+            .filter(|variables| variables.is_empty())
+            .and(message.value())
+            .map(|pattern| (id.clone(), pattern));
+        let attributes = shape
+            .attributes
+            .iter()
+            .filter(|(_, variables)| variables.is_empty())
+            .filter_map(|(attribute, _)| {
+                let pattern = message.get_attribute(attribute)?.value();
+                Some(([id, ".", attribute].concat(), pattern))
+            });
+        for (key, pattern) in value.into_iter().chain(attributes) {
+            let mut errors = vec![];
+            let text = bundle.format_pattern(pattern, None, &mut errors);
+            if errors.is_empty() {
+                texts.insert(key, text.into_owned());
+            }
+        }
+    }
+    texts
+}
+
 fn load_ftl(cache: &assets_manager::AnyCache, id: &assets_manager::SharedString) -> Option<String> {
     match cache.load::<FluentFilePath>(id) {
         Ok(lang_file) => {
@@ -61,6 +261,127 @@ fn load_ftl(cache: &assets_manager::AnyCache, id: &assets_manager::SharedString)
         }
     }
 }
+
+/// Parse the Fluent file `file`, keeping every entry that parses. Syntax
+/// errors and entries defined before in `definitions` are diagnosed.
+fn add_ftl(
+    file: &str,
+    source: String,
+    definitions: &mut HashMap<String, FtlLocation>,
+    diagnostics: &mut Vec<FtlDiagnostic>,
+) -> FluentResource {
+    let resource = FluentResource::try_new(source).unwrap_or_else(|(resource, errors)| {
+        diagnostics.extend(errors.iter().map(|err| FtlDiagnostic {
+            location: FtlLocation::new(file, resource.source(), err.pos.start),
+            kind: FtlDiagnosticKind::Syntax(err.to_string()),
+        }));
+        resource
+    });
+    let mut offsets = entry_offsets(resource.source());
+    for entry in resource.entries() {
+        let id = match entry {
+            ast::Entry::Message(message) => message.id.name.to_string(),
+            ast::Entry::Term(term) => ["-", term.id.name].concat(),
+            _ => continue,
+        };
+        let offset = offsets
+            .get_mut(&id)
+            .and_then(|offsets| offsets.pop_front())
+            .unwrap_or_default();
+        let location = FtlLocation::new(file, resource.source(), offset);
+        match definitions.get(&id) {
+            Some(first) => diagnostics.push(FtlDiagnostic {
+                location,
+                kind: FtlDiagnosticKind::Override {
+                    id,
+                    first: first.clone(),
+                },
+            }),
+            None => {
+                definitions.insert(id, location);
+            }
+        }
+    }
+    resource
+}
+
+/// Where every message and term of `source` starts, in order. Entries
+/// always start at the beginning of a line with their id.
+fn entry_offsets(source: &str) -> HashMap<String, VecDeque<usize>> {
+    let mut offsets: HashMap<String, VecDeque<usize>> = HashMap::new();
+    let mut offset = 0;
+    for line in source.split_inclusive('\n') {
+        let id_len = line
+            .char_indices()
+            .find(|&(_, c)| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+            .map_or(line.len(), |(i, _)| i);
+        let (id, rest) = line.split_at(id_len);
+        if !id.is_empty() && rest.trim_start().starts_with('=') {
+            offsets.entry(id.to_string()).or_default().push_back(offset);
+        }
+        offset += line.len();
+    }
+    offsets
+}
+
+/// [`FtlDiagnostic`] is a problem found while loading the Fluent files of a
+/// language pack. The messages it concerns are skipped or overridden, the rest
+/// of the pack is still usable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FtlDiagnostic {
+    pub location: FtlLocation,
+    pub kind: FtlDiagnosticKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FtlDiagnosticKind {
+    /// The entry is not valid Fluent and is skipped.
+    Syntax(String),
+    /// A message or term, `-` prefixed, is defined again. The definition at
+    /// `first` is kept.
+    Override { id: String, first: FtlLocation },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FtlLocation {
+    /// Asset id of the Fluent file.
+    pub file: String,
+    /// Line and column, both starting at 1.
+    pub line: usize,
+    pub column: usize,
+}
+
+impl FtlLocation {
+    fn new(file: &str, source: &str, offset: usize) -> FtlLocation {
+        let before = &source[..offset.min(source.len())];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        FtlLocation {
+            file: file.to_string(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for FtlLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+impl fmt::Display for FtlDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            FtlDiagnosticKind::Syntax(message) => write!(f, "{}: {}", self.location, message),
+            FtlDiagnosticKind::Override { id, first } => write!(
+                f,
+                "{}: {} is already defined at {}",
+                self.location, id, first
+            ),
+        }
+    }
+}
+
 struct FluentFilePath(String);
 
 impl From<String> for FluentFilePath {
@@ -76,9 +397,9 @@ impl assets_manager::Asset for FluentFilePath {
 
 #[test]
 fn test_load_language_pack() {
-    use crate::data::source::ASSETS;
     crate::tests_utils::logging_init();
-    let lang = ASSETS.load::<LanguagePack>("en").unwrap();
+    let assets = crate::tests_utils::assets();
+    let lang = assets.cache().load::<LanguagePack>("en").unwrap();
     assert_eq!(
         lang.read().locale,
         "en".parse::<LanguageIdentifier>().unwrap()
@@ -86,6 +407,118 @@ fn test_load_language_pack() {
     assert!(lang.read().bundle.get_message("Cleric").is_some());
 }
 
+#[test]
+fn test_ftl_diagnostics() {
+    let mut definitions = HashMap::new();
+    let mut diagnostics = vec![];
+    let first = "hello = Hello\nbroken { $who }\n-brand = Mutemaanpa\n";
+    add_ftl(
+        "language.en.a",
+        first.to_string(),
+        &mut definitions,
+        &mut diagnostics,
+    );
+    let second = "bye = Bye\n\nhello = Hi\n";
+    let resource = add_ftl(
+        "language.en.b",
+        second.to_string(),
+        &mut definitions,
+        &mut diagnostics,
+    );
+    assert_eq!(resource.entries().count(), 2);
+
+    assert_eq!(diagnostics.len(), 2);
+    assert!(matches!(diagnostics[0].kind, FtlDiagnosticKind::Syntax(_)));
+    assert_eq!(
+        (diagnostics[0].location.line, diagnostics[0].location.column),
+        (2, 8)
+    );
+    assert_eq!(
+        diagnostics[1].to_string(),
+        "language.en.b:3:1: hello is already defined at language.en.a:1:1"
+    );
+    assert!(definitions.contains_key("-brand"));
+}
+
+#[test]
+fn test_available_languages() {
+    crate::tests_utils::logging_init();
+    let languages = LanguageFileDataSource::new(crate::tests_utils::assets()).available_languages();
+    let tags: Vec<String> = languages.iter().map(|l| l.locale.to_string()).collect();
+    assert_eq!(tags, vec!["en", "en-GB", "zh-CN"]);
+    assert_eq!(languages[0].name, "English");
+    assert_eq!(languages[0].completeness, Some(1.0));
+    assert!(languages[1].completeness.unwrap() < 0.1);
+    assert_eq!(languages[2].name, "简体中文");
+    assert!(languages[2].fonts.contains_key(&FontRole::Regular));
+}
+
+#[test]
+fn test_text_direction() {
+    assert_eq!(
+        TextDirection::of(&langid!("en")),
+        TextDirection::LeftToRight
+    );
+    assert_eq!(
+        TextDirection::of(&langid!("zh-CN")),
+        TextDirection::LeftToRight
+    );
+    assert_eq!(
+        TextDirection::of(&langid!("ar")),
+        TextDirection::RightToLeft
+    );
+    assert_eq!(
+        TextDirection::of(&langid!("he-IL")),
+        TextDirection::RightToLeft
+    );
+    // Azerbaijani is written in Latin or in Arabic.
+    assert_eq!(
+        TextDirection::of(&langid!("az-Arab")),
+        TextDirection::RightToLeft
+    );
+}
+
+#[test]
+fn test_missing_language_pack() {
+    crate::tests_utils::logging_init();
+    let source = LanguageFileDataSource::new(crate::tests_utils::assets());
+    assert!(matches!(
+        source.get_language_pack("xx".parse().unwrap()),
+        Err(Error::MissingLanguagePack(_))
+    ));
+    assert!(source.get_language_pack_strict(langid!("en")).is_ok());
+}
+
+#[test]
+fn test_strict_language_pack() {
+    crate::tests_utils::logging_init();
+    let root = tempfile::tempdir().unwrap();
+    let german = root.path().join("language").join("de");
+    std::fs::create_dir_all(&german).unwrap();
+    std::fs::write(german.join("manifest.yaml"), "{language: de, fonts: {}}").unwrap();
+    std::fs::write(
+        german.join("broken.ftl"),
+        "hello = Hallo\nbroken { $who }\n",
+    )
+    .unwrap();
+    let source = LanguageFileDataSource::new(Assets::open(root.path(), &[]).unwrap());
+
+    // The broken entry is skipped, unless the caller asks for strict loading.
+    let pack = source.get_language_pack(langid!("de")).unwrap();
+    assert!(pack.read().bundle.get_message("hello").is_some());
+    match source.get_language_pack_strict(langid!("de")) {
+        Err(Error::LanguagePack {
+            locale,
+            diagnostics,
+        }) => {
+            assert_eq!(locale, langid!("de"));
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].location.line, 2);
+        }
+        _ => panic!("broken Fluent loaded strictly"),
+    }
+}
+
 /// [`LanguageManifest`] is the language-pack level metadata, containing the fonts
 /// and language identifier. It is used to load/store directly so its fields are public and plain.
 /// Because we can't serialize [`LanguageIdentifier`], so this struct
@@ -93,15 +526,34 @@ fn test_load_language_pack() {
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
 pub struct LanguageManifest {
     pub fonts: Fonts,
+    /// Fonts for the scripts the fonts above do not cover, like the names of
+    /// foreign characters. Tried in order.
+    #[serde(default)]
+    pub fallback_fonts: Vec<Font>,
     pub language: String,
+    /// Name of the language in itself, like `Deutsch`. Manifests written
+    /// before it existed leave it empty, see [`LanguageManifest::name`].
+    #[serde(default)]
+    pub name: String,
+}
+
+impl LanguageManifest {
+    /// Name of the language in itself, its tag when the manifest has none.
+    pub fn name(&self) -> &str {
+        match self.name.is_empty() {
+            true => &self.language,
+            false => &self.name,
+        }
+    }
 }
 
 /// [`Fonts`] contains all fonts used in a set of translation.
 /// We are often in need of multiple fonts to achieve good display effect.
 /// At least there are sans-serif and serif fonts.
-pub type Fonts = HashMap<String, Font>;
+pub type Fonts = HashMap<FontRole, Font>;
 
-/// [`Font`] contains the location of a font file.
+/// [`Font`] contains the location of a font file, see
+/// [`FontFileDataSource::get_font`](super::font::FontFileDataSource::get_font).
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
 pub struct Font {
     pub location: String,
@@ -114,24 +566,40 @@ fn test_manifest() {
         fonts: {
             let mut map = HashMap::new();
             map.insert(
-                String::from("regular"),
+                FontRole::Regular,
                 Font {
                     location: String::from("font.ttf"),
                 },
             );
             map.insert(
-                String::from("serif"),
+                FontRole::Serif,
                 Font {
                     location: String::from("font2.ttf"),
                 },
             );
             map
         },
+        fallback_fonts: vec![],
         language: "en-US".to_string(),
+        name: "English (US)".to_string(),
     };
     let manifest_str = serde_yaml::to_string(&manifest);
     let recovered_struct: LanguageManifest = serde_yaml::from_str(&manifest_str.unwrap()).unwrap();
     assert_eq!(manifest, recovered_struct);
+
+    let unnamed: LanguageManifest = serde_yaml::from_str("{language: de, fonts: {}}").unwrap();
+    assert_eq!(unnamed.name(), "de");
+}
+
+impl LanguagePack {
+    /// Diagnostics of the entries skipped because they are not valid Fluent.
+    pub fn syntax_errors(&self) -> Vec<FtlDiagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| matches!(d.kind, FtlDiagnosticKind::Syntax(_)))
+            .cloned()
+            .collect()
+    }
 }
 
 /// Language manifest is loaded by [`assets_manager`].
@@ -140,20 +608,164 @@ impl assets_manager::Asset for LanguageManifest {
     type Loader = assets_manager::loader::YamlLoader;
 }
 
-pub struct LanguageFileDataSource;
+/// [`LanguageInfo`] describes an installed language pack, for language pickers.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LanguageInfo {
+    pub locale: LanguageIdentifier,
+    /// Name of the language in itself.
+    pub name: String,
+    pub direction: TextDirection,
+    pub fonts: Fonts,
+    /// Share of the English messages translated, see [`LanguagePack::completeness`].
+    /// None when the pack cannot be loaded.
+    pub completeness: Option<f32>,
+}
+
+pub struct LanguageFileDataSource {
+    assets: Arc<Assets>,
+}
 
 impl LanguageFileDataSource {
-    pub fn new() -> LanguageFileDataSource {
-        LanguageFileDataSource
+    pub fn new(assets: Arc<Assets>) -> LanguageFileDataSource {
+        LanguageFileDataSource { assets }
     }
 
-    pub fn get_language_pack(&self, lang: LanguageIdentifier) -> Handle<'static, LanguagePack> {
-        ASSETS.load(&lang.to_string()).unwrap()
+    /// The pack of `lang`, or English transformed for [`PSEUDO_LOCALE`].
+    pub fn get_language_pack(
+        &self,
+        lang: LanguageIdentifier,
+    ) -> crate::Result<AssetHandle<LanguagePack>> {
+        let id = lang.to_string();
+        let dir = match lang == PSEUDO_LOCALE {
+            true => "language.en".to_string(),
+            false => ["language.", &id].concat(),
+        };
+        let pack = match self
+            .assets
+            .cache()
+            .raw_source()
+            .exists(DirEntry::Directory(&dir))
+        {
+            true => self.assets.load(&id),
+            false => Err(Error::MissingLanguagePack(lang.clone())),
+        };
+        match pack {
+            // The assets may miss or break English, the embedded pack cannot.
+            Err(err) if lang == langid!("en") => {
+                warn!(
+                    "English from the assets is not usable, using the embedded one: {}",
+                    err
+                );
+                Assets::embedded().load(&id)
+            }
+            pack => pack,
+        }
     }
-}
 
-impl Default for LanguageFileDataSource {
-    fn default() -> Self {
-        Self::new()
+    /// Like [`LanguageFileDataSource::get_language_pack`], failing with
+    /// [`Error::LanguagePack`] when a Fluent file of the pack has syntax
+    /// errors instead of skipping the broken entries.
+    pub fn get_language_pack_strict(
+        &self,
+        lang: LanguageIdentifier,
+    ) -> crate::Result<AssetHandle<LanguagePack>> {
+        let pack = self.get_language_pack(lang.clone())?;
+        let diagnostics = pack.read().syntax_errors();
+        match diagnostics.is_empty() {
+            true => Ok(pack),
+            false => Err(Error::LanguagePack {
+                locale: lang,
+                diagnostics,
+            }),
+        }
+    }
+
+    /// Locales of the language packs under `language/`, whether they load or
+    /// not, and English.
+    pub fn installed_languages(&self) -> Vec<LanguageIdentifier> {
+        let mut languages: Vec<LanguageIdentifier> = self
+            .language_dirs()
+            .iter()
+            .filter_map(|dir| dir.strip_prefix("language.")?.parse().ok())
+            .collect();
+        // English missing from the assets is read from the embedded pack.
+        if !languages.contains(&langid!("en")) {
+            languages.push(langid!("en"));
+        }
+        languages.sort_by_key(|l| l.to_string());
+        languages
+    }
+
+    /// Every language pack under `language/` with a valid manifest, and the
+    /// embedded English when the assets have none, sorted by language
+    /// identifier.
+    pub fn available_languages(&self) -> Vec<LanguageInfo> {
+        let english = self.get_language_pack(langid!("en")).ok();
+        let mut languages: Vec<LanguageInfo> = self
+            .language_dirs()
+            .iter()
+            .filter_map(|dir| self.language_info(dir, english.as_ref()))
+            .collect();
+        if !languages.iter().any(|l| l.locale == langid!("en")) {
+            let embedded = LanguageFileDataSource::new(Assets::embedded());
+            languages.extend(embedded.language_info("language.en", english.as_ref()));
+        }
+        languages.sort_by_key(|l| l.locale.to_string());
+        languages
+    }
+
+    /// Asset ids of the directories under `language/`.
+    fn language_dirs(&self) -> Vec<String> {
+        let mut dirs = vec![];
+        let source = self.assets.cache().raw_source();
+        let read = source.read_dir("language", &mut |entry| {
+            if let DirEntry::Directory(id) = entry {
+                dirs.push(id.to_string());
+            }
+        });
+        if let Err(err) = read {
+            warn!("cannot list language packs: {}", err);
+        }
+        dirs
+    }
+
+    fn language_info(
+        &self,
+        dir: &str,
+        english: Option<&AssetHandle<LanguagePack>>,
+    ) -> Option<LanguageInfo> {
+        let tag = dir.strip_prefix("language.")?;
+        let manifest = self
+            .assets
+            .cache()
+            .load::<LanguageManifest>(&[dir, ".manifest"].concat())
+            .inspect_err(|err| warn!("skip language pack {}: {}", tag, err.reason()))
+            .ok()?
+            .cloned();
+        if manifest.language != tag {
+            warn!(
+                "skip language pack {}: its manifest is for {}",
+                tag, manifest.language
+            );
+            return None;
+        }
+        let locale: LanguageIdentifier = tag
+            .parse()
+            .inspect_err(|err| warn!("skip language pack {}: {}", tag, err))
+            .ok()?;
+        let completeness = match self.get_language_pack(locale.clone()) {
+            Ok(pack) => english.map(|english| pack.read().completeness(&english.read())),
+            Err(err) => {
+                warn!("language pack {} cannot be loaded: {}", tag, err);
+                None
+            }
+        };
+        Some(LanguageInfo {
+            direction: TextDirection::of(&locale),
+            locale,
+            name: manifest.name().to_string(),
+            fonts: manifest.fonts,
+            completeness,
+        })
     }
 }
@@ -0,0 +1,210 @@
+//! # Layered asset source
+//!
+//! [`LayeredSource`] stacks asset folders: the base game first, then every
+//! enabled mod in load order. A file is read from the last layer that has it,
+//! and directories list the files of every layer, so a mod can replace
+//! `gameplay/classes.yaml` as well as add `language/en/quests.ftl`. Layers are
+//! folders, or archives written by [`pack`](super::packed::pack).
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, io,
+};
+
+use assets_manager::{
+    hot_reloading::{DynUpdateSender, EventSender, FsWatcherBuilder},
+    source::{DirEntry, FileContent, FileSystem, Source},
+    BoxedError,
+};
+
+use super::{mods::ModManifest, packed::PackedSource};
+
+/// Name of the layer of the base game.
+pub const BASE_LAYER: &str = "base";
+
+#[derive(Clone, Debug)]
+pub struct LayeredSource {
+    /// The base game first, the layer read first last.
+    layers: Vec<Layer>,
+}
+
+#[derive(Clone, Debug)]
+struct Layer {
+    name: String,
+    files: LayerFiles,
+}
+
+/// [`LayerFiles`] are the files of a layer.
+#[derive(Clone, Debug)]
+pub enum LayerFiles {
+    Folder(FileSystem),
+    Packed(PackedSource),
+}
+
+impl LayerFiles {
+    fn source(&self) -> &dyn Source {
+        match self {
+            LayerFiles::Folder(files) => files,
+            LayerFiles::Packed(files) => files,
+        }
+    }
+}
+
+impl From<FileSystem> for LayerFiles {
+    fn from(files: FileSystem) -> Self {
+        LayerFiles::Folder(files)
+    }
+}
+
+impl From<PackedSource> for LayerFiles {
+    fn from(files: PackedSource) -> Self {
+        LayerFiles::Packed(files)
+    }
+}
+
+/// [`LayerConflict`] is a file provided by more than one mod. Only the last
+/// one is used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayerConflict {
+    /// Path of the file relative to the asset folders.
+    pub path: String,
+    /// The mods providing the file, in load order.
+    pub layers: Vec<String>,
+}
+
+impl fmt::Display for LayerConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is provided by {}, {} is used",
+            self.path,
+            self.layers.join(", "),
+            self.layers.last().map_or("none", |layer| layer.as_str())
+        )
+    }
+}
+
+impl LayeredSource {
+    pub fn new(base: impl Into<LayerFiles>) -> LayeredSource {
+        LayeredSource {
+            layers: vec![Layer {
+                name: BASE_LAYER.to_string(),
+                files: base.into(),
+            }],
+        }
+    }
+
+    /// Put `files` on top of the others.
+    pub fn push(&mut self, name: &str, files: impl Into<LayerFiles>) {
+        self.layers.push(Layer {
+            name: name.to_string(),
+            files: files.into(),
+        });
+    }
+
+    /// Names of the layers, the base game first.
+    pub fn layers(&self) -> impl Iterator<Item = &str> {
+        self.layers.iter().map(|layer| layer.name.as_str())
+    }
+
+    /// Name of the layer `entry` is read from.
+    pub fn provider(&self, entry: DirEntry) -> Option<&str> {
+        self.layers
+            .iter()
+            .rev()
+            .find(|layer| layer.files.source().exists(entry))
+            .map(|layer| layer.name.as_str())
+    }
+
+    /// Files provided by more than one mod, sorted by path. Mods replacing
+    /// files of the base game are not conflicts, nor their manifests.
+    pub fn conflicts(&self) -> Vec<LayerConflict> {
+        let mut providers: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for layer in self.layers.iter().skip(1) {
+            let mut files = vec![];
+            list_files(layer.files.source(), "", &mut files);
+            for path in files.into_iter().filter(|p| p != ModManifest::FILE) {
+                providers.entry(path).or_default().push(layer.name.clone());
+            }
+        }
+        providers
+            .into_iter()
+            .filter(|(_, layers)| layers.len() > 1)
+            .map(|(path, layers)| LayerConflict { path, layers })
+            .collect()
+    }
+}
+
+/// Paths of every file under the directory `id`, like `language/en/classes.ftl`.
+fn list_files(files: &dyn Source, id: &str, paths: &mut Vec<String>) {
+    let mut dirs = vec![];
+    let _ = files.read_dir(id, &mut |entry| match entry {
+        DirEntry::File(id, ext) => paths.push(format!("{}.{}", id.replace('.', "/"), ext)),
+        DirEntry::Directory(id) => dirs.push(id.to_string()),
+    });
+    for dir in dirs {
+        list_files(files, &dir, paths);
+    }
+}
+
+impl Source for LayeredSource {
+    fn read(&self, id: &str, ext: &str) -> io::Result<FileContent<'_>> {
+        for layer in self.layers.iter().rev() {
+            match layer.files.source().read(id, ext) {
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                result => return result,
+            }
+        }
+        Err(io::ErrorKind::NotFound.into())
+    }
+
+    fn read_dir(&self, id: &str, f: &mut dyn FnMut(DirEntry)) -> io::Result<()> {
+        let mut files = BTreeSet::new();
+        let mut found = false;
+        for layer in self.layers.iter() {
+            let read = layer.files.source().read_dir(id, &mut |entry| {
+                let (id, ext) = match entry {
+                    DirEntry::File(id, ext) => (id, Some(ext)),
+                    DirEntry::Directory(id) => (id, None),
+                };
+                files.insert((id.to_string(), ext.map(str::to_string)));
+            });
+            match read {
+                Ok(()) => found = true,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+        if !found {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        for (id, ext) in files.iter() {
+            match ext {
+                Some(ext) => f(DirEntry::File(id, ext)),
+                None => f(DirEntry::Directory(id)),
+            }
+        }
+        Ok(())
+    }
+
+    fn exists(&self, entry: DirEntry) -> bool {
+        self.layers
+            .iter()
+            .any(|layer| layer.files.source().exists(entry))
+    }
+
+    fn make_source(&self) -> Option<Box<dyn Source + Send>> {
+        Some(Box::new(self.clone()))
+    }
+
+    fn configure_hot_reloading(&self, events: EventSender) -> Result<DynUpdateSender, BoxedError> {
+        let mut watcher = FsWatcherBuilder::new()?;
+        // Archives do not change while the game runs.
+        for layer in self.layers.iter() {
+            if let LayerFiles::Folder(files) = &layer.files {
+                watcher.watch(files.root().to_path_buf())?;
+            }
+        }
+        Ok(watcher.build(events))
+    }
+}
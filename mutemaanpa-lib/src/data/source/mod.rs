@@ -1,8 +1,10 @@
-use assets_manager::AssetCache;
-use once_cell::sync::Lazy;
-
-pub const ASSETS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../assets");
+pub mod assets;
+pub mod class;
+pub mod dialogue;
+pub mod font;
+pub mod functions;
 pub mod language;
-
-/// Data Source from the Asset folder
-pub static ASSETS: Lazy<AssetCache> = Lazy::new(|| AssetCache::new(ASSETS_DIR).unwrap());
+pub mod layered;
+pub mod mods;
+pub mod packed;
+pub mod pseudo;
@@ -0,0 +1,310 @@
+//! # Mods
+//!
+//! A mod is a folder of `mods/`, next to the asset folder, laid out like
+//! `assets/`, with a `mod.yaml`
+//! manifest:
+//!
+//! ```yaml
+//! id: more-classes
+//! name: More Classes
+//! version: 1.0.0
+//! dependencies: [better-texts]
+//! load_order: 10
+//! ```
+//!
+//! Enabled mods are stacked on the base game by [`LayeredSource`]: mods with
+//! a lower load order first, and every mod after its dependencies. A mod whose
+//! dependencies cannot be loaded is skipped and reported.
+
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use assets_manager::source::FileSystem;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::layered::{LayerFiles, LayeredSource};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ModManifest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    /// Ids of the mods this one builds on, they are loaded before it.
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    /// Mods with a lower load order are loaded first, ties are broken by id.
+    #[serde(default)]
+    pub load_order: i32,
+}
+
+impl ModManifest {
+    pub const FILE: &'static str = "mod.yaml";
+}
+
+/// [`Mod`] is a mod folder with a valid manifest.
+#[derive(Clone, Debug)]
+pub struct Mod {
+    pub manifest: ModManifest,
+    pub root: PathBuf,
+}
+
+/// [`ModProblem`] is a mod that cannot be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModProblem {
+    /// The folder has no readable manifest.
+    InvalidManifest { path: String, reason: String },
+    /// Another folder already has a mod with this id.
+    DuplicateId { id: String, path: String },
+    /// The mod is enabled in the settings but not installed.
+    NotInstalled { id: String },
+    /// A dependency of the mod is not installed, not enabled or skipped.
+    MissingDependency { id: String, dependency: String },
+    /// The mod depends on itself through other mods.
+    DependencyCycle { id: String },
+}
+
+impl fmt::Display for ModProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidManifest { path, reason } => {
+                write!(f, "mod {} skipped: invalid manifest: {}", path, reason)
+            }
+            Self::DuplicateId { id, path } => {
+                write!(f, "mod {} skipped: {} is already installed", path, id)
+            }
+            Self::NotInstalled { id } => write!(f, "mod {} is enabled but not installed", id),
+            Self::MissingDependency { id, dependency } => {
+                write!(f, "mod {} skipped: it depends on {}", id, dependency)
+            }
+            Self::DependencyCycle { id } => {
+                write!(f, "mod {} skipped: its dependencies form a cycle", id)
+            }
+        }
+    }
+}
+
+/// [`ModList`] is every installed mod, and the enabled ones in load order.
+#[derive(Clone, Debug, Default)]
+pub struct ModList {
+    installed: Vec<Mod>,
+    enabled: Vec<Mod>,
+    problems: Vec<ModProblem>,
+}
+
+impl ModList {
+    /// Find the mods installed in `dir` and order the `enabled` ones.
+    pub fn load(dir: impl AsRef<Path>, enabled: &[String]) -> ModList {
+        let mut list = ModList::default();
+        list.find_installed(dir.as_ref());
+        for id in enabled.iter() {
+            if list.installed.iter().all(|m| m.manifest.id != *id) {
+                list.problems
+                    .push(ModProblem::NotInstalled { id: id.clone() });
+            }
+        }
+        let mut candidates: Vec<Mod> = list
+            .installed
+            .iter()
+            .filter(|m| enabled.contains(&m.manifest.id))
+            .cloned()
+            .collect();
+        candidates.sort_by(|a, b| {
+            (a.manifest.load_order, &a.manifest.id).cmp(&(b.manifest.load_order, &b.manifest.id))
+        });
+        list.order(candidates);
+        for problem in list.problems.iter() {
+            warn!("{}", problem);
+        }
+        for m in list.enabled.iter() {
+            info!("mod {} {} enabled", m.manifest.id, m.manifest.version);
+        }
+        list
+    }
+
+    fn find_installed(&mut self, dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        let mut roots: Vec<PathBuf> = entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.is_dir())
+            .collect();
+        roots.sort();
+        for root in roots {
+            let path = root.display().to_string();
+            let manifest = fs::read_to_string(root.join(ModManifest::FILE))
+                .map_err(|err| err.to_string())
+                .and_then(|s| serde_yaml::from_str::<ModManifest>(&s).map_err(|e| e.to_string()));
+            match manifest {
+                Err(reason) => self
+                    .problems
+                    .push(ModProblem::InvalidManifest { path, reason }),
+                Ok(manifest) if self.installed.iter().any(|m| m.manifest.id == manifest.id) => {
+                    self.problems.push(ModProblem::DuplicateId {
+                        id: manifest.id,
+                        path,
+                    })
+                }
+                Ok(manifest) => self.installed.push(Mod { manifest, root }),
+            }
+        }
+    }
+
+    /// Enable `candidates`, sorted by load order, each after its dependencies.
+    fn order(&mut self, mut candidates: Vec<Mod>) {
+        // Skipping a mod may leave its dependents without a dependency.
+        while let Some(i) = candidates.iter().position(|m| {
+            m.manifest
+                .dependencies
+                .iter()
+                .any(|d| candidates.iter().all(|c| c.manifest.id != *d))
+        }) {
+            let skipped = candidates.remove(i);
+            let dependency = skipped
+                .manifest
+                .dependencies
+                .iter()
+                .find(|d| candidates.iter().all(|c| c.manifest.id != **d))
+                .cloned()
+                .unwrap_or_default();
+            self.problems.push(ModProblem::MissingDependency {
+                id: skipped.manifest.id,
+                dependency,
+            });
+        }
+        while !candidates.is_empty() {
+            let ready = candidates.iter().position(|m| {
+                m.manifest
+                    .dependencies
+                    .iter()
+                    .all(|d| self.enabled.iter().any(|e| e.manifest.id == *d))
+            });
+            match ready {
+                Some(i) => self.enabled.push(candidates.remove(i)),
+                None => {
+                    for m in candidates.drain(..) {
+                        self.problems
+                            .push(ModProblem::DependencyCycle { id: m.manifest.id });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Every mod with a valid manifest, sorted by folder.
+    pub fn installed(&self) -> &[Mod] {
+        &self.installed
+    }
+
+    /// The enabled mods that can be loaded, in load order.
+    pub fn enabled(&self) -> &[Mod] {
+        &self.enabled
+    }
+
+    pub fn problems(&self) -> &[ModProblem] {
+        &self.problems
+    }
+
+    /// The `base` assets with the enabled mods on top.
+    pub fn source(&self, base: impl Into<LayerFiles>) -> LayeredSource {
+        let mut source = LayeredSource::new(base);
+        for m in self.enabled.iter() {
+            match FileSystem::new(&m.root) {
+                Ok(files) => source.push(&m.manifest.id, files),
+                Err(err) => warn!("mod {} skipped: {}", m.manifest.id, err),
+            }
+        }
+        source
+    }
+}
+
+#[test]
+fn test_mod_list() {
+    use super::{assets::ASSETS_DIR, language::LanguagePack};
+    use assets_manager::AssetCache;
+    crate::tests_utils::logging_init();
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path().join("mods");
+    let install = |folder: &str, manifest: &str, files: &[(&str, &str)]| {
+        let root = dir.join(folder);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join(ModManifest::FILE), manifest).unwrap();
+        for (path, content) in files {
+            fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+            fs::write(root.join(path), content).unwrap();
+        }
+    };
+    let greeting = "language/en/mod.ftl";
+    install(
+        "quests",
+        "{id: quests, name: Quests, version: 1.0.0}",
+        &[(greeting, "mod-greeting = Quests")],
+    );
+    install(
+        "texts",
+        "{id: texts, name: Texts, version: 0.1.0, dependencies: [quests], load_order: -1}",
+        &[(greeting, "mod-greeting = Texts")],
+    );
+    install(
+        "orphan",
+        "{id: orphan, name: Orphan, version: 1.0.0, dependencies: [missing]}",
+        &[],
+    );
+    install(
+        "cycle",
+        "{id: cycle, name: Cycle, version: 1.0.0, dependencies: [cycle]}",
+        &[],
+    );
+    install("broken", "id: [", &[]);
+    install(
+        "quests-copy",
+        "{id: quests, name: Copy, version: 1.0.0}",
+        &[],
+    );
+
+    let enabled = ["texts", "quests", "orphan", "cycle", "absent"].map(String::from);
+    let list = ModList::load(&dir, &enabled);
+    assert_eq!(list.installed().len(), 4);
+    let order: Vec<&str> = list
+        .enabled()
+        .iter()
+        .map(|m| m.manifest.id.as_str())
+        .collect();
+    // Texts has a lower load order, but depends on quests.
+    assert_eq!(order, ["quests", "texts"]);
+    let problems = list.problems();
+    assert_eq!(problems.len(), 5);
+    assert!(problems.contains(&ModProblem::NotInstalled {
+        id: "absent".into()
+    }));
+    assert!(problems.contains(&ModProblem::MissingDependency {
+        id: "orphan".into(),
+        dependency: "missing".into()
+    }));
+    assert!(problems.contains(&ModProblem::DependencyCycle { id: "cycle".into() }));
+    assert!(problems
+        .iter()
+        .any(|p| matches!(p, ModProblem::DuplicateId { id, .. } if id == "quests")));
+
+    let source = list.source(FileSystem::new(ASSETS_DIR).unwrap());
+    let conflicts = source.conflicts();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].path, greeting);
+    assert_eq!(conflicts[0].layers, ["quests", "texts"]);
+
+    // Mods add Fluent files to the packs of the base game.
+    let cache = AssetCache::with_source(source);
+    let english = cache.load::<LanguagePack>("en").unwrap().read();
+    let format = |id: &str| {
+        let pattern = english.bundle.get_message(id).unwrap().value().unwrap();
+        english
+            .bundle
+            .format_pattern(pattern, None, &mut vec![])
+            .into_owned()
+    };
+    assert_eq!(format("mod-greeting"), "Texts");
+    assert_eq!(format("Cleric"), "Cleric");
+}
@@ -0,0 +1,304 @@
+//! # Packed assets
+//!
+//! Release builds ship the asset folder as a single archive, written by
+//! [`pack`] and read by [`PackedSource`]. Assets load the same from the
+//! archive as from the folder it was packed from.
+//!
+//! The archive starts with an index of the files, then their contents:
+//!
+//! ```text
+//! magic    b"MTMPACK1"
+//! count    u32
+//! count ×  path length u32, path (UTF-8, like `language/en/classes.ftl`),
+//!          offset u64, length u64
+//! contents
+//! ```
+//!
+//! Integers are little-endian, offsets start after the index.
+//!
+//! The English pack is also compiled into the library, see
+//! [`PackedSource::embedded`].
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs, io,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use assets_manager::source::{DirEntry, FileContent, Source};
+use once_cell::sync::Lazy;
+
+/// Extension of the archives.
+pub const PACK_EXTENSION: &str = "pack";
+
+const MAGIC: &[u8; 8] = b"MTMPACK1";
+
+/// [`PackedSource`] is an archive written by [`pack`], held in memory.
+#[derive(Clone, Debug)]
+pub struct PackedSource {
+    pack: Arc<Pack>,
+}
+
+#[derive(Debug)]
+struct Pack {
+    data: Vec<u8>,
+    /// Range of the contents in `data`, by id and extension.
+    files: HashMap<(String, String), Range<usize>>,
+    /// Files and directories of every directory, by id.
+    dirs: HashMap<String, BTreeSet<(String, Option<String>)>>,
+}
+
+impl PackedSource {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<PackedSource> {
+        Self::from_bytes(fs::read(path)?)
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> io::Result<PackedSource> {
+        let invalid = |reason: &str| io::Error::new(io::ErrorKind::InvalidData, reason);
+        let mut reader = Reader { data: &data, at: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(invalid("not an asset archive"));
+        }
+        let count = reader.u32()?;
+        let mut index = vec![];
+        for _ in 0..count {
+            let length = reader.u32()? as usize;
+            let path = std::str::from_utf8(reader.take(length)?)
+                .map_err(|_| invalid("file path is not UTF-8"))?
+                .to_string();
+            let offset = reader.u64()? as usize;
+            let length = reader.u64()? as usize;
+            index.push((path, offset, length));
+        }
+        let start = reader.at;
+        let mut files = HashMap::new();
+        let mut dirs: HashMap<String, BTreeSet<(String, Option<String>)>> = HashMap::new();
+        dirs.insert(String::new(), BTreeSet::new());
+        for (path, offset, length) in index {
+            let range =
+                start.saturating_add(offset)..start.saturating_add(offset).saturating_add(length);
+            if range.start > range.end || range.end > data.len() {
+                return Err(invalid("file contents out of the archive"));
+            }
+            let (id, ext) =
+                asset_id(Path::new(&path)).ok_or_else(|| invalid("file path is not an asset"))?;
+            // Register the file and every directory above it.
+            let mut child = (id.clone(), Some(ext.clone()));
+            let mut parent = id.as_str();
+            while !parent.is_empty() {
+                parent = parent.rfind('.').map_or("", |dot| &parent[..dot]);
+                let new = !dirs.contains_key(parent);
+                dirs.entry(parent.to_string()).or_default().insert(child);
+                if !new {
+                    break;
+                }
+                child = (parent.to_string(), None);
+            }
+            files.insert((id, ext), range);
+        }
+        Ok(PackedSource {
+            pack: Arc::new(Pack { data, files, dirs }),
+        })
+    }
+
+    /// The English pack compiled into the library, read when the assets
+    /// have none. It is packed once, copies share it.
+    pub fn embedded() -> PackedSource {
+        static EMBEDDED: Lazy<PackedSource> = Lazy::new(|| {
+            let files = EMBEDDED_FILES.iter().map(|(path, data)| (*path, *data));
+            PackedSource::from_bytes(archive(files))
+                .expect("the embedded English pack is packed at build time")
+        });
+        EMBEDDED.clone()
+    }
+
+    /// Number of files in the archive.
+    pub fn len(&self) -> usize {
+        self.pack.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pack.files.is_empty()
+    }
+}
+
+impl Source for PackedSource {
+    fn read(&self, id: &str, ext: &str) -> io::Result<FileContent<'_>> {
+        let range = self
+            .pack
+            .files
+            .get(&(id.to_string(), ext.to_string()))
+            .ok_or(io::ErrorKind::NotFound)?;
+        Ok(FileContent::Slice(&self.pack.data[range.clone()]))
+    }
+
+    fn read_dir(&self, id: &str, f: &mut dyn FnMut(DirEntry)) -> io::Result<()> {
+        let entries = self.pack.dirs.get(id).ok_or(io::ErrorKind::NotFound)?;
+        for (id, ext) in entries.iter() {
+            match ext {
+                Some(ext) => f(DirEntry::File(id, ext)),
+                None => f(DirEntry::Directory(id)),
+            }
+        }
+        Ok(())
+    }
+
+    fn exists(&self, entry: DirEntry) -> bool {
+        match entry {
+            DirEntry::File(id, ext) => self
+                .pack
+                .files
+                .contains_key(&(id.to_string(), ext.to_string())),
+            DirEntry::Directory(id) => self.pack.dirs.contains_key(id),
+        }
+    }
+
+    fn make_source(&self) -> Option<Box<dyn Source + Send>> {
+        Some(Box::new(self.clone()))
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> io::Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.at..self.at + length)
+            .ok_or(io::ErrorKind::UnexpectedEof)?;
+        self.at += length;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+/// The id and extension of the asset at `path`, relative to the asset folder.
+/// Files without an extension, like `.gitattributes`, are not assets.
+fn asset_id(path: &Path) -> Option<(String, String)> {
+    let ext = path.extension()?.to_str()?;
+    let stem = path.file_stem()?.to_str()?;
+    let mut parts: Vec<&str> = path
+        .parent()?
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect::<Option<_>>()?;
+    parts.push(stem);
+    if parts
+        .iter()
+        .any(|part| part.is_empty() || part.contains('.'))
+    {
+        return None;
+    }
+    Some((parts.join("."), ext.to_string()))
+}
+
+/// Files of the folder `dir` that are assets, relative to it, sorted.
+fn asset_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(relative) = dirs.pop() {
+        for entry in fs::read_dir(dir.join(&relative))? {
+            let entry = entry?;
+            let path = relative.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                dirs.push(path);
+            } else if asset_id(&path).is_some() {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Pack the assets of the folder `dir` into the archive `output`, returning
+/// the packed files.
+pub fn pack(dir: impl AsRef<Path>, output: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    let files = asset_files(dir)?;
+    let mut contents = vec![];
+    for file in files.iter() {
+        // Archives are read on every platform, paths use `/`.
+        let path: Vec<String> = file
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        contents.push((path.join("/"), fs::read(dir.join(file))?));
+    }
+    let archive = archive(
+        contents
+            .iter()
+            .map(|(path, data)| (path.as_str(), data.as_slice())),
+    );
+    fs::write(output, archive)?;
+    Ok(files)
+}
+
+/// An archive of `files`, by path in the assets.
+fn archive<'a>(files: impl ExactSizeIterator<Item = (&'a str, &'a [u8])>) -> Vec<u8> {
+    let mut index = vec![];
+    let mut contents = vec![];
+    index.extend_from_slice(MAGIC);
+    index.extend_from_slice(&(files.len() as u32).to_le_bytes());
+    for (path, data) in files {
+        index.extend_from_slice(&(path.len() as u32).to_le_bytes());
+        index.extend_from_slice(path.as_bytes());
+        index.extend_from_slice(&(contents.len() as u64).to_le_bytes());
+        index.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        contents.extend_from_slice(data);
+    }
+    index.extend_from_slice(&contents);
+    index
+}
+
+include!(concat!(env!("OUT_DIR"), "/embedded.rs"));
+
+#[test]
+fn test_pack() {
+    use super::{assets::ASSETS_DIR, language::LanguagePack};
+    use assets_manager::AssetCache;
+    crate::tests_utils::logging_init();
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("assets.pack");
+    let files = pack(ASSETS_DIR, &output).unwrap();
+    assert!(files.contains(&Path::new("language").join("en").join("classes.ftl")));
+    assert!(!files.iter().any(|f| f.ends_with(".gitattributes")));
+
+    let packed = PackedSource::open(&output).unwrap();
+    assert_eq!(packed.len(), files.len());
+    assert!(packed.exists(DirEntry::Directory("language.zh-CN")));
+    assert!(packed.exists(DirEntry::File("fonts.NotoSans-Regular", "ttf")));
+    let mut languages = vec![];
+    packed
+        .read_dir("language", &mut |entry| {
+            languages.push(entry.id().to_string())
+        })
+        .unwrap();
+    assert!(languages.contains(&"language.en".to_string()));
+    assert!(languages.contains(&"language.test".to_string()));
+
+    // Language packs load the same from the folder and from the archive.
+    let assets = crate::tests_utils::assets();
+    let folder = assets.cache();
+    let archive = AssetCache::with_source(packed);
+    for lang in ["en", "zh-CN"] {
+        let expected = folder.load::<LanguagePack>(lang).unwrap().read();
+        let found = archive.load::<LanguagePack>(lang).unwrap().read();
+        assert_eq!(found.messages, expected.messages);
+    }
+
+    fs::write(&output, b"MTMPACK1\x01\x00").unwrap();
+    assert!(PackedSource::open(&output).is_err());
+}
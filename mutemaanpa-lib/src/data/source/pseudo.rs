@@ -0,0 +1,52 @@
+//! # Pseudo-localization
+//!
+//! The pseudo-locale [`PSEUDO_LOCALE`] is English made to look foreign: letters
+//! are accented, texts are padded about 40% longer and every message is
+//! bracketed. A text shown plainly in it is hard-coded, and a missing bracket
+//! means it was truncated.
+//!
+//! It has no pack of its own, [`LanguagePack`](super::language::LanguagePack)
+//! builds it from the English files with [`transform`] so placeables are kept.
+//! The brackets are added by the text repository around whole messages.
+
+use std::borrow::Cow;
+
+use unic_langid::{langid, LanguageIdentifier};
+
+pub const PSEUDO_LOCALE: LanguageIdentifier = langid!("en-XA");
+
+const LOWERCASE: [char; 26] = [
+    'å', 'ƀ', 'ç', 'ð', 'é', 'ƒ', 'ĝ', 'ĥ', 'î', 'ĵ', 'ķ', 'ļ', 'ɱ', 'ñ', 'ö', 'þ', 'ǫ', 'ŕ', 'š',
+    'ţ', 'û', 'ṽ', 'ŵ', 'ẋ', 'ý', 'ž',
+];
+
+const UPPERCASE: [char; 26] = [
+    'Å', 'Ɓ', 'Ç', 'Ð', 'É', 'Ƒ', 'Ĝ', 'Ĥ', 'Î', 'Ĵ', 'Ķ', 'Ļ', 'Ṁ', 'Ñ', 'Ö', 'Þ', 'Ǫ', 'Ŕ', 'Š',
+    'Ţ', 'Û', 'Ṽ', 'Ŵ', 'Ẋ', 'Ý', 'Ž',
+];
+
+/// Accent and pad a text element of a message, for
+/// [`FluentBundle::set_transform`](fluent_bundle::bundle::FluentBundle::set_transform).
+pub fn transform(text: &str) -> Cow<'_, str> {
+    let mut length: usize = 0;
+    let mut pseudo: String = text
+        .chars()
+        .map(|c| {
+            length += 1;
+            match c {
+                'a'..='z' => LOWERCASE[c as usize - 'a' as usize],
+                'A'..='Z' => UPPERCASE[c as usize - 'A' as usize],
+                _ => c,
+            }
+        })
+        .collect();
+    pseudo.extend(std::iter::repeat_n('~', (length * 2).div_ceil(5)));
+    Cow::Owned(pseudo)
+}
+
+#[test]
+fn test_transform() {
+    assert_eq!(transform("Hello, "), "Ĥéļļö, ~~~");
+    assert_eq!(transform(""), "");
+    assert_eq!(transform("牧师"), "牧师~");
+}
@@ -0,0 +1,124 @@
+//! # Errors
+//!
+//! [`Error`] is returned by every fallible entry point of the library, so a
+//! front-end can tell the player what went wrong instead of crashing.
+
+use std::{fmt, io, path::PathBuf};
+
+use unic_langid::{LanguageIdentifier, LanguageIdentifierError};
+
+use crate::{data::source::language::FtlDiagnostic, save::SaveError};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The settings file cannot be read or written.
+    SettingIo {
+        path: String,
+        source: io::Error,
+    },
+    /// The settings file is not a valid setting.
+    SettingParse {
+        path: String,
+        source: serde_lexpr::Error,
+    },
+    /// A setting is out of its range, or conflicts with another one.
+    InvalidSetting {
+        name: String,
+        reason: String,
+    },
+    /// A language is not a valid language identifier.
+    InvalidLanguage {
+        tag: String,
+        source: LanguageIdentifierError,
+    },
+    /// No language pack is shipped for the language.
+    MissingLanguagePack(LanguageIdentifier),
+    /// Fluent files of a language pack loaded strictly have syntax errors.
+    LanguagePack {
+        locale: LanguageIdentifier,
+        diagnostics: Vec<FtlDiagnostic>,
+    },
+    /// The asset folder cannot be opened.
+    AssetRoot {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// A font location is not a font file name, like `NotoSans-Regular.ttf`.
+    InvalidFontLocation(String),
+    /// No mod with the id is installed.
+    UnknownMod(String),
+    /// Any other asset that cannot be loaded, like a malformed class tree.
+    Asset(assets_manager::Error),
+    Save(SaveError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SettingIo { path, source } => {
+                write!(f, "cannot access settings {}: {}", path, source)
+            }
+            Self::SettingParse { path, source } => {
+                write!(f, "malformed settings {}: {}", path, source)
+            }
+            Self::InvalidSetting { name, reason } => {
+                write!(f, "invalid setting {}: {}", name, reason)
+            }
+            Self::InvalidLanguage { tag, source } => {
+                write!(f, "invalid language {}: {}", tag, source)
+            }
+            Self::MissingLanguagePack(lang) => write!(f, "no language pack for {}", lang),
+            Self::LanguagePack {
+                locale,
+                diagnostics,
+            } => {
+                write!(f, "broken Fluent in language pack {}", locale)?;
+                for diagnostic in diagnostics.iter() {
+                    write!(f, "\n{}", diagnostic)?;
+                }
+                Ok(())
+            }
+            Self::AssetRoot { path, source } => {
+                write!(f, "cannot open assets {}: {}", path.display(), source)
+            }
+            Self::InvalidFontLocation(location) => {
+                write!(f, "invalid font location {}", location)
+            }
+            Self::UnknownMod(id) => write!(f, "no mod {} is installed", id),
+            Self::Asset(err) => write!(f, "cannot load {}: {}", err.id(), err.reason()),
+            Self::Save(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::SettingIo { source, .. } => Some(source),
+            Self::SettingParse { source, .. } => Some(source),
+            Self::InvalidSetting { .. } => None,
+            Self::InvalidLanguage { source, .. } => Some(source),
+            Self::MissingLanguagePack(_) => None,
+            Self::LanguagePack { .. } => None,
+            Self::AssetRoot { source, .. } => Some(source),
+            Self::InvalidFontLocation(_) => None,
+            Self::UnknownMod(_) => None,
+            Self::Asset(err) => Some(err.reason()),
+            Self::Save(err) => Some(err),
+        }
+    }
+}
+
+impl From<assets_manager::Error> for Error {
+    fn from(err: assets_manager::Error) -> Self {
+        Self::Asset(err)
+    }
+}
+
+impl From<SaveError> for Error {
+    fn from(err: SaveError) -> Self {
+        Self::Save(err)
+    }
+}
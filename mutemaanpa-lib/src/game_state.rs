@@ -1,77 +1,698 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crate::{
-    data::{repository::text::TextRepository, source::language::LanguageFileDataSource},
-    gameplay::class::{ClassTree, ClassTreeDescription},
-    setting::Setting,
+    data::{
+        repository::{font::FontRepository, text::TextRepository},
+        source::{
+            assets::{AssetHandle, Assets},
+            class::ClassFileDataSource,
+            dialogue::DialogueFileDataSource,
+            font::FontFileDataSource,
+            language::LanguageFileDataSource,
+        },
+    },
+    gameplay::{
+        attribute::Attributes,
+        character::Character,
+        class::{ClassIdentifier, ClassOption, ClassRejection, ClassTree, ClassTreeDescription},
+        dialogue::{Dialogue, DialogueContext, DialogueError, DialogueLine},
+        skill::{Skill, SkillDescription},
+    },
+    save::{DialogueSave, SaveData, SaveError, SaveFile, SaveMetadata, SaveSlots, SlotNumber},
+    setting::{Setting, SettingChange},
+    Error,
 };
-use tracing::info;
-use unic_langid::LanguageIdentifier;
+use fluent_bundle::FluentArgs;
+use tracing::{info, warn};
 
 /// [`GameState`] stores states that shared by whole game.
 ///
 /// It is layered as below:
 ///
 /// 1. Connection layer
-///     This layer connects the game backend to UI layer, provides an event API for
-/// them to communicate. The backend does not need to know how UI display the game,
+///    This layer connects the game backend to UI layer, provides an event API for
+///    them to communicate. The backend does not need to know how UI display the game,
 ///
 /// 2. Gameplay layer
-///     This layer deals with game logic, processes data and give them to the UI.
+///    This layer deals with game logic, processes data and give them to the UI.
 ///
 /// 3. Data layer
-///     This layer provides the game data from various data sources like local assets,
-/// mods or the Internet.
+///    This layer provides the game data from various data sources like local assets,
+///    mods or the Internet.
 pub struct GameState {
+    assets: Arc<Assets>,
     setting: Setting,
     class_tree: ClassTree,
+    class_tree_handle: AssetHandle<ClassTree>,
+    /// Texts of the class tree, kept until the tree or the language changes.
+    class_descriptions: HashMap<String, ClassTreeDescription>,
+    character: Option<Character>,
+    /// Story flags, set as the story unfolds.
+    flags: HashSet<String>,
+    dialogue: Option<Dialogue>,
+    saves: SaveSlots,
+    /// Play time of the loaded save, before this session.
+    play_time: Duration,
+    session_start: Instant,
+    events: Vec<Event>,
     pub text: TextRepository,
+    pub fonts: FontRepository,
 }
 
-impl Default for GameState {
-    fn default() -> Self {
-        let setting = match Setting::load(Setting::DEFAULT_SETTINGS_PATH) {
+impl GameState {
+    /// Load the settings and the game data of the asset folder `assets`, see
+    /// [`resolve_root`](crate::data::source::assets::resolve_root). Fails if the game data is
+    /// broken, or if missing settings cannot be written. Settings that cannot be read are
+    /// replaced by the defaults for the session, their file is left as it is.
+    pub fn new(assets: impl AsRef<Path>) -> crate::Result<GameState> {
+        Self::with_setting_path(assets, Setting::DEFAULT_SETTINGS_PATH)
+    }
+
+    /// Like [`GameState::new`], with the settings at `path`.
+    pub fn with_setting_path(assets: impl AsRef<Path>, path: &str) -> crate::Result<GameState> {
+        let setting = match Setting::load(path) {
             Ok(setting) => {
                 info!("find user setting {:?}", setting);
                 setting
             }
+            Err(Error::SettingIo { source, .. }) if source.kind() == io::ErrorKind::NotFound => {
+                info!("Did not find user setting, using default");
+                let setting = Setting::new(path);
+                setting.save()?;
+                setting
+            }
             Err(err) => {
-                info!("Did not find user setting, using default: {}", err);
-                Setting::default()
+                warn!("cannot read user setting, using default: {}", err);
+                Setting::new(path)
             }
         };
-        setting.save().expect("setting configuration cannot save");
-        let text_source = LanguageFileDataSource::new();
-        let mut text = TextRepository::new(text_source);
-        text.load(setting.language.language.parse().unwrap());
-        Self {
+        let assets = Assets::open(assets, &setting.mods)?;
+        let text_source = LanguageFileDataSource::new(assets.clone());
+        let mut text = TextRepository::new(text_source)?;
+        // A mod providing the language may have been disabled since.
+        if let Err(err) = text.change_languages(&setting.language.chain()) {
+            warn!(
+                "cannot use the language of the settings, using English: {}",
+                err
+            );
+        }
+        let fonts = FontRepository::new(FontFileDataSource::new(assets.clone()), &text);
+        let class_tree_handle = ClassFileDataSource::new(assets.clone()).get_class_tree()?;
+        let class_tree = class_tree_handle.cloned();
+        Ok(Self {
+            assets,
             setting,
-            class_tree: ClassTree::default(),
+            class_descriptions: class_tree.get_descriptions(&text),
+            class_tree,
+            class_tree_handle,
+            character: None,
+            flags: HashSet::new(),
+            dialogue: None,
+            saves: SaveSlots::default(),
+            play_time: Duration::ZERO,
+            session_start: Instant::now(),
+            events: vec![],
             text,
-        }
+            fonts,
+        })
     }
 }
 
 #[derive(Debug, Clone)]
 pub enum Command {
-    ChangeLanguage(LanguageIdentifier),
+    /// Switch to the language of a tag like `zh-CN`, see
+    /// [`TextRepository::change_language`].
+    ChangeLanguage(String),
+    /// Enable an installed mod from the next start, see
+    /// [`mods`](crate::data::source::mods).
+    EnableMod(String),
+    /// Disable a mod from the next start.
+    DisableMod(String),
+    /// Change an audio, video, controls or gameplay setting, see
+    /// [`Setting::change`].
+    ChangeSetting(SettingChange),
+    /// Create the player character, replacing the current one.
+    CreateCharacter {
+        name: String,
+        attributes: Attributes,
+    },
+    RenameCharacter(String),
+    GainExperience(u32),
+    TakeClass(ClassIdentifier),
+    Damage(u32),
+    Heal(u32),
+    /// Start the dialogue stored under `dialogue/<id>`.
+    StartDialogue(String),
+    /// Pick a choice of the current dialogue line, see [`Dialogue::choose`].
+    ChooseDialogue(usize),
+    Save(SlotNumber),
+    Load(SlotNumber),
+}
+
+/// [`Event`]s are sent from the game to the UI, telling it what to refresh.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// Every text and font has to be fetched again.
+    LanguageChanged,
+    /// The audio, video, controls or gameplay settings have to be applied.
+    SettingChanged,
+    /// A setting was changed for this session only.
+    SettingSaveFailed {
+        reason: String,
+    },
+    /// The class tree asset was edited and the class tree has been rebuilt.
+    ClassTreeChanged,
+    /// The player character was created or modified.
+    CharacterChanged,
+    /// The player character reached a new level.
+    LevelUp {
+        level: u32,
+    },
+    /// The player character could not take a class.
+    ClassRejected {
+        class: ClassIdentifier,
+        rejections: Vec<ClassRejection>,
+    },
+    /// The dialogue moved to another line.
+    DialogueChanged,
+    /// The dialogue is over.
+    DialogueEnded,
+    Saved {
+        slot: SlotNumber,
+    },
+    /// A save was loaded, everything about the character and the dialogue
+    /// has to be refreshed.
+    Loaded {
+        slot: SlotNumber,
+    },
+    /// Saving or loading failed, the game state is unchanged.
+    SaveFailed {
+        slot: SlotNumber,
+        reason: String,
+    },
 }
 
 impl GameState {
-    pub fn get_skill_tree(&self) -> (&ClassTree, HashMap<String, ClassTreeDescription>) {
-        (
-            &self.class_tree,
-            self.class_tree.get_descriptions(&self.text),
-        )
+    /// The asset folder of the game, with its mods.
+    pub fn assets(&self) -> &Arc<Assets> {
+        &self.assets
+    }
+
+    pub fn setting(&self) -> &Setting {
+        &self.setting
+    }
+
+    pub fn get_skill_tree(&self) -> (&ClassTree, &HashMap<String, ClassTreeDescription>) {
+        (&self.class_tree, &self.class_descriptions)
+    }
+
+    /// Every skill owned through the given class path, with its description.
+    pub fn get_skills(&self, classes: &[ClassIdentifier]) -> Vec<(Skill, SkillDescription)> {
+        self.class_tree
+            .skills(classes)
+            .into_iter()
+            .map(|skill| {
+                let description = skill.get_description(&self.text);
+                (skill, description)
+            })
+            .collect()
+    }
+
+    pub fn character(&self) -> Option<&Character> {
+        self.character.as_ref()
+    }
+
+    /// Every skill the player character owns through its classes.
+    pub fn get_character_skills(&self) -> Vec<(Skill, SkillDescription)> {
+        self.character
+            .as_ref()
+            .map(|c| self.get_skills(&c.classes))
+            .unwrap_or_default()
+    }
+
+    /// Classes the player character can take next, see [`ClassTree::next_classes`].
+    pub fn next_classes(&self) -> Vec<ClassOption> {
+        self.character
+            .as_ref()
+            .map(|c| self.class_tree.next_classes(&c.class_progress()))
+            .unwrap_or_default()
+    }
+
+    /// The current line of the dialogue being played, if any.
+    pub fn dialogue_line(&self) -> Option<DialogueLine> {
+        self.dialogue
+            .as_ref()
+            .and_then(|d| d.current_node(&self.text, self))
+    }
+
+    /// The choices of the current dialogue line.
+    pub fn dialogue_choices(&self) -> Result<Vec<String>, DialogueError> {
+        self.dialogue
+            .as_ref()
+            .map(|d| d.choices(&self.text, self))
+            .unwrap_or(Ok(vec![]))
+    }
+
+    pub fn play_time(&self) -> Duration {
+        self.play_time + self.session_start.elapsed()
+    }
+
+    /// Used save slots, see [`SaveSlots::list`].
+    pub fn list_saves(&self) -> Vec<(SlotNumber, Result<SaveMetadata, SaveError>)> {
+        self.saves.list()
     }
 
-    pub fn command_handler(&mut self, command: Command) {
+    /// The gameplay part of the game state, ready to be saved.
+    pub fn save_file(&self) -> SaveFile {
+        let dialogue = self.dialogue.as_ref().and_then(|d| {
+            Some(DialogueSave {
+                id: d.id.clone(),
+                node: d.current_id()?.clone(),
+            })
+        });
+        let data = SaveData {
+            character: self.character.clone(),
+            flags: self.flags.iter().cloned().collect(),
+            dialogue,
+        };
+        SaveFile::new(data, self.play_time())
+    }
+
+    /// Replace the gameplay part of the game state with `save`. Nothing is
+    /// changed if the save cannot be restored.
+    pub fn restore(&mut self, save: SaveFile) -> Result<(), SaveError> {
+        let dialogue = match save.data.dialogue {
+            Some(DialogueSave { id, node }) => {
+                let graph = DialogueFileDataSource::new(self.assets.clone())
+                    .get_dialogue(&id)
+                    .map_err(|err| SaveError::Dialogue {
+                        id: id.clone(),
+                        reason: err.to_string(),
+                    })?;
+                let dialogue = Dialogue::resume(&id, graph.cloned(), &node).ok_or_else(|| {
+                    SaveError::Dialogue {
+                        id: id.clone(),
+                        reason: format!("node {} no longer exists", node),
+                    }
+                })?;
+                Some(dialogue)
+            }
+            None => None,
+        };
+        self.character = save.data.character;
+        self.flags = save.data.flags.into_iter().collect();
+        self.dialogue = dialogue;
+        self.play_time = Duration::from_secs(save.metadata.play_time);
+        self.session_start = Instant::now();
+        Ok(())
+    }
+
+    /// Pick up changes from the data layer, should be called once per frame.
+    /// Returns the [`Event`]s the UI has to react to, including those caused
+    /// by commands since the last call.
+    pub fn update(&mut self) -> Vec<Event> {
+        self.assets.hot_reload();
+        let mut events = std::mem::take(&mut self.events);
+        if self.class_tree_handle.reloaded() {
+            info!("class tree reloaded");
+            self.class_tree = self.class_tree_handle.cloned();
+            self.class_descriptions = self.class_tree.get_descriptions(&self.text);
+            events.push(Event::ClassTreeChanged);
+        }
+        if self.text.reloaded() {
+            info!("language packs reloaded");
+            self.class_descriptions = self.class_tree.get_descriptions(&self.text);
+            self.fonts.resolve(&self.text);
+            if !events.contains(&Event::LanguageChanged) {
+                events.push(Event::LanguageChanged);
+            }
+        }
+        events
+    }
+
+    /// Handle a command from the UI. Commands whose failure has to be shown to
+    /// the player right away return an error, the others report what happened
+    /// through [`Event`]s.
+    pub fn command_handler(&mut self, command: Command) -> crate::Result<()> {
         match command {
-            Command::ChangeLanguage(lang) => {
-                self.setting.language.language = lang.to_string();
-                self.setting.save().unwrap();
-                self.text.load(lang);
+            Command::ChangeLanguage(tag) => return self.change_language(&tag),
+            Command::EnableMod(id) => return self.enable_mod(id),
+            Command::ChangeSetting(change) => return self.change_setting(change),
+            Command::DisableMod(id) => self.disable_mod(&id),
+            Command::CreateCharacter { name, attributes } => {
+                self.create_character(&name, attributes)
             }
+            Command::RenameCharacter(name) => self.update_character("rename", |character, _, _| {
+                character.name = name;
+                true
+            }),
+            Command::GainExperience(experience) => {
+                self.update_character("gain experience", |character, _, events| {
+                    let gained = character.gain_experience(experience);
+                    for level in character.level - gained + 1..=character.level {
+                        events.push(Event::LevelUp { level });
+                    }
+                    true
+                })
+            }
+            Command::TakeClass(class) => {
+                self.update_character("take a class", |character, tree, events| {
+                    match character.take_class(tree, &class) {
+                        Ok(()) => true,
+                        Err(rejections) => {
+                            info!("cannot take class {}: {:?}", class, rejections);
+                            events.push(Event::ClassRejected { class, rejections });
+                            false
+                        }
+                    }
+                })
+            }
+            Command::Damage(amount) => self.update_character("damage", |character, _, _| {
+                character.damage(amount);
+                true
+            }),
+            Command::Heal(amount) => self.update_character("heal", |character, _, _| {
+                character.heal(amount);
+                true
+            }),
+            Command::StartDialogue(id) => self.start_dialogue(&id),
+            Command::ChooseDialogue(choice) => self.choose_dialogue(choice),
+            Command::Save(slot) => self.save(slot),
+            Command::Load(slot) => self.load(slot),
+        }
+        Ok(())
+    }
+
+    /// Switch to another language and remember it in the settings. Nothing
+    /// changes if there is no language pack for it. If the settings cannot be
+    /// saved, the language is changed for this session only, see
+    /// [`Event::SettingSaveFailed`].
+    fn change_language(&mut self, tag: &str) -> crate::Result<()> {
+        let mut language = self.setting.language.clone();
+        language.language = tag.to_string();
+        self.text
+            .change_languages(&language.chain())
+            .inspect_err(|err| warn!("cannot change language: {}", err))?;
+        info!("language changed to {}", tag);
+        self.fonts.resolve(&self.text);
+        self.class_descriptions = self.class_tree.get_descriptions(&self.text);
+        self.events.push(Event::LanguageChanged);
+        self.setting.language = language;
+        self.save_setting();
+        Ok(())
+    }
+
+    /// Write the settings, telling the UI when they could not be written.
+    fn save_setting(&mut self) {
+        if let Err(err) = self.setting.save() {
+            warn!("cannot save settings: {}", err);
+            self.events.push(Event::SettingSaveFailed {
+                reason: err.to_string(),
+            });
+        }
+    }
+
+    /// Fails with [`Error::UnknownMod`] when no mod `id` is installed.
+    fn enable_mod(&mut self, id: String) -> crate::Result<()> {
+        let installed = self.assets.mods().installed();
+        if !installed.iter().any(|m| m.manifest.id == id) {
+            return Err(Error::UnknownMod(id));
+        }
+        if !self.setting.mods.contains(&id) {
+            self.setting.mods.push(id);
+        }
+        self.save_setting();
+        Ok(())
+    }
+
+    fn disable_mod(&mut self, id: &str) {
+        self.setting.mods.retain(|m| m != id);
+        self.save_setting();
+    }
+
+    /// Fails when the change is invalid. When the settings cannot be saved,
+    /// the change is kept for this session only, see
+    /// [`Event::SettingSaveFailed`].
+    fn change_setting(&mut self, change: SettingChange) -> crate::Result<()> {
+        self.setting
+            .change(change)
+            .inspect_err(|err| warn!("cannot change setting: {}", err))?;
+        self.events.push(Event::SettingChanged);
+        self.save_setting();
+        Ok(())
+    }
+
+    fn create_character(&mut self, name: &str, attributes: Attributes) {
+        info!("create character {}", name);
+        self.character = Some(Character::new(name, attributes, &self.class_tree));
+        self.events.push(Event::CharacterChanged);
+    }
+
+    /// Apply `update` to the player character, if there is one. `update`
+    /// returns whether the character changed.
+    fn update_character(
+        &mut self,
+        action: &str,
+        update: impl FnOnce(&mut Character, &ClassTree, &mut Vec<Event>) -> bool,
+    ) {
+        let Some(character) = self.character.as_mut() else {
+            warn!("no character to {}", action);
+            return;
+        };
+        if update(character, &self.class_tree, &mut self.events) {
+            self.events.push(Event::CharacterChanged);
+        }
+    }
+
+    fn start_dialogue(&mut self, id: &str) {
+        let graph = match DialogueFileDataSource::new(self.assets.clone()).get_dialogue(id) {
+            Ok(graph) => graph,
+            Err(err) => {
+                warn!("cannot start dialogue {}: {}", id, err);
+                return;
+            }
+        };
+        let before = self.character.clone();
+        match Dialogue::new(id, graph.cloned(), self) {
+            Ok(dialogue) => {
+                info!("start dialogue {}", id);
+                self.dialogue = Some(dialogue);
+                self.events.push(Event::DialogueChanged);
+            }
+            Err(err) => warn!("cannot start dialogue {}: {}", id, err),
+        }
+        self.character_events(before);
+    }
+
+    fn choose_dialogue(&mut self, choice: usize) {
+        let Some(mut dialogue) = self.dialogue.take() else {
+            warn!("no dialogue to choose {} from", choice);
+            return;
+        };
+        let before = self.character.clone();
+        let result = dialogue.choose(choice, self);
+        self.character_events(before);
+        match result {
+            Ok(()) if dialogue.is_finished() => {
+                self.events.push(Event::DialogueEnded);
+                return;
+            }
+            Ok(()) => self.events.push(Event::DialogueChanged),
+            Err(err) => warn!(
+                "cannot choose {} in dialogue {}: {}",
+                choice, dialogue.id, err
+            ),
+        }
+        self.dialogue = Some(dialogue);
+    }
+
+    fn save(&mut self, slot: SlotNumber) {
+        match self.saves.save(slot, &self.save_file()) {
+            Ok(()) => self.events.push(Event::Saved { slot }),
+            Err(err) => self.save_failed(slot, err),
+        }
+    }
+
+    fn load(&mut self, slot: SlotNumber) {
+        match self.saves.load(slot).and_then(|save| self.restore(save)) {
+            Ok(()) => {
+                info!("loaded slot {}", slot);
+                self.events.push(Event::Loaded { slot });
+            }
+            Err(err) => self.save_failed(slot, err),
+        }
+    }
+
+    fn save_failed(&mut self, slot: SlotNumber, err: SaveError) {
+        warn!("saving or loading slot {} failed: {}", slot, err);
+        self.events.push(Event::SaveFailed {
+            slot,
+            reason: err.to_string(),
+        });
+    }
+
+    /// Tell the UI about changes dialogue effects made to the character.
+    fn character_events(&mut self, before: Option<Character>) {
+        let (Some(before), Some(after)) = (before, self.character.as_ref()) else {
+            return;
+        };
+        if &before == after {
+            return;
+        }
+        for level in before.level + 1..=after.level {
+            self.events.push(Event::LevelUp { level });
+        }
+        self.events.push(Event::CharacterChanged);
+    }
+}
+
+impl DialogueContext for GameState {
+    fn has_flag(&self, flag: &str) -> bool {
+        self.flags.contains(flag)
+    }
+
+    fn set_flag(&mut self, flag: &str, set: bool) {
+        info!("flag {} set to {}", flag, set);
+        match set {
+            true => self.flags.insert(flag.to_string()),
+            false => self.flags.remove(flag),
+        };
+    }
+
+    fn character(&self) -> Option<&Character> {
+        self.character.as_ref()
+    }
+
+    fn character_mut(&mut self) -> Option<&mut Character> {
+        self.character.as_mut()
+    }
+
+    fn args(&self) -> FluentArgs<'_> {
+        let mut args = FluentArgs::new();
+        if let Some(character) = self.character.as_ref() {
+            args.set("player", character.name.as_str());
+            args.set("pronouns", character.pronouns.id());
+            args.set("class", character.classes.last().map(String::as_str));
+        }
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, thread};
+
+    use super::*;
+    use crate::data::source::assets::ASSETS_DIR;
+
+    const CLASSES: &str = "classes:
+  - name: Base
+    children: [Fighter]
+  - name: Fighter
+";
+
+    #[test]
+    fn test_setting_commands() {
+        crate::tests_utils::logging_init();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.cfg");
+        let mut game_state =
+            GameState::with_setting_path(ASSETS_DIR, path.to_str().unwrap()).unwrap();
+        game_state.update();
+        // The settings cannot be written over a directory.
+        fs::remove_file(&path).unwrap();
+        fs::create_dir(&path).unwrap();
+
+        let change = SettingChange::MusicVolume(30);
+        game_state
+            .command_handler(Command::ChangeSetting(change))
+            .unwrap();
+        assert_eq!(game_state.setting().audio.music_volume, 30);
+        let events = game_state.update();
+        assert!(events.contains(&Event::SettingChanged));
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, Event::SettingSaveFailed { .. })));
+
+        // Only installed mods can be enabled.
+        let enable = Command::EnableMod("not-installed".to_string());
+        assert!(matches!(
+            game_state.command_handler(enable),
+            Err(Error::UnknownMod(id)) if id == "not-installed"
+        ));
+        assert!(game_state.setting().mods.is_empty());
+    }
+
+    #[test]
+    fn test_class_tree_hot_reload() {
+        crate::tests_utils::logging_init();
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("assets");
+        let classes = root.join("gameplay").join("classes.yaml");
+        fs::create_dir_all(classes.parent().unwrap()).unwrap();
+        fs::write(&classes, CLASSES).unwrap();
+        let setting_path = dir.path().join("settings.cfg");
+        let mut game_state =
+            GameState::with_setting_path(&root, setting_path.to_str().unwrap()).unwrap();
+        assert!(!game_state.get_skill_tree().1.contains_key("Cleric"));
+
+        fs::write(
+            &classes,
+            CLASSES.replace("[Fighter]", "[Fighter, Cleric]") + "  - name: Cleric\n",
+        )
+        .unwrap();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !game_state.update().contains(&Event::ClassTreeChanged) {
+            assert!(Instant::now() < deadline, "the class tree was not reloaded");
+            thread::sleep(Duration::from_millis(50));
+        }
+        let (tree, descriptions) = game_state.get_skill_tree();
+        assert_eq!(tree.root.children.len(), 2);
+        assert_eq!(descriptions["Cleric"].name, "Cleric");
+        assert_eq!(
+            descriptions["Cleric"].detail,
+            "They unite the team and society through ideology and myths."
+        );
+    }
+
+    #[test]
+    fn test_language_hot_reload() {
+        crate::tests_utils::logging_init();
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("assets");
+        let classes = root.join("gameplay").join("classes.yaml");
+        fs::create_dir_all(classes.parent().unwrap()).unwrap();
+        fs::write(&classes, CLASSES).unwrap();
+        let english = root.join("language").join("en");
+        fs::create_dir_all(&english).unwrap();
+        fs::write(english.join("manifest.yaml"), "{language: en, fonts: {}}").unwrap();
+        fs::write(english.join("classes.ftl"), "Fighter = Fighter").unwrap();
+        let setting_path = dir.path().join("settings.cfg");
+        let mut game_state =
+            GameState::with_setting_path(&root, setting_path.to_str().unwrap()).unwrap();
+        assert!(game_state.fonts.problems().is_empty());
+
+        // Fonts of an edited manifest are looked for again.
+        fs::write(
+            english.join("manifest.yaml"),
+            "{language: en, fonts: {regular: {location: Missing.ttf}}}",
+        )
+        .unwrap();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !game_state.update().contains(&Event::LanguageChanged) {
+            assert!(
+                Instant::now() < deadline,
+                "the language pack was not reloaded"
+            );
+            thread::sleep(Duration::from_millis(50));
         }
+        assert_eq!(game_state.fonts.problems().len(), 1);
+        assert_eq!(game_state.fonts.problems()[0].location, "Missing.ttf");
     }
 }
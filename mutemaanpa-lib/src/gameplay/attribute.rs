@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+/// # Attributes
+///
+/// Core attributes describe what a character is born with. They gate classes
+/// and drive most checks in the game.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Attribute {
+    Strength,
+    Dexterity,
+    Constitution,
+    Intelligence,
+    Wisdom,
+    Charisma,
+}
+
+impl Attribute {
+    pub const ALL: [Attribute; 6] = [
+        Attribute::Strength,
+        Attribute::Dexterity,
+        Attribute::Constitution,
+        Attribute::Intelligence,
+        Attribute::Wisdom,
+        Attribute::Charisma,
+    ];
+
+    /// Name of the attribute in the assets, like `strength`. Its localized
+    /// name is the message `stat-<id>`.
+    pub fn id(self) -> &'static str {
+        match self {
+            Attribute::Strength => "strength",
+            Attribute::Dexterity => "dexterity",
+            Attribute::Constitution => "constitution",
+            Attribute::Intelligence => "intelligence",
+            Attribute::Wisdom => "wisdom",
+            Attribute::Charisma => "charisma",
+        }
+    }
+}
+
+/// [`Attributes`] holds a score for every [`Attribute`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attributes {
+    pub strength: u32,
+    pub dexterity: u32,
+    pub constitution: u32,
+    pub intelligence: u32,
+    pub wisdom: u32,
+    pub charisma: u32,
+}
+
+impl Default for Attributes {
+    fn default() -> Self {
+        Self {
+            strength: Self::AVERAGE,
+            dexterity: Self::AVERAGE,
+            constitution: Self::AVERAGE,
+            intelligence: Self::AVERAGE,
+            wisdom: Self::AVERAGE,
+            charisma: Self::AVERAGE,
+        }
+    }
+}
+
+impl Attributes {
+    /// Score of an average person.
+    pub const AVERAGE: u32 = 10;
+
+    pub fn get(&self, attribute: Attribute) -> u32 {
+        match attribute {
+            Attribute::Strength => self.strength,
+            Attribute::Dexterity => self.dexterity,
+            Attribute::Constitution => self.constitution,
+            Attribute::Intelligence => self.intelligence,
+            Attribute::Wisdom => self.wisdom,
+            Attribute::Charisma => self.charisma,
+        }
+    }
+
+    pub fn set(&mut self, attribute: Attribute, value: u32) {
+        match attribute {
+            Attribute::Strength => self.strength = value,
+            Attribute::Dexterity => self.dexterity = value,
+            Attribute::Constitution => self.constitution = value,
+            Attribute::Intelligence => self.intelligence = value,
+            Attribute::Wisdom => self.wisdom = value,
+            Attribute::Charisma => self.charisma = value,
+        }
+    }
+}
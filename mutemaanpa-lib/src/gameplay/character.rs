@@ -0,0 +1,287 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    attribute::Attributes,
+    class::{ClassIdentifier, ClassProgress, ClassRejection, ClassTree},
+};
+
+/// # Characters
+///
+/// A [`Character`] is the player (or anyone acting like one): who it is, how
+/// experienced it is and which path it took through the [`ClassTree`].
+/// It knows nothing about how it is displayed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Character {
+    pub name: String,
+    pub level: u32,
+    /// Total experience earned since level 1.
+    pub experience: u32,
+    pub attributes: Attributes,
+    pub hit_points: HitPoints,
+    /// Points left to spend on classes, earned when leveling up.
+    pub class_points: u32,
+    /// Classes taken, in the order they were taken. The first one is always
+    /// the root of the class tree.
+    pub classes: Vec<ClassIdentifier>,
+    /// Items carried, by item id, never holding a zero count.
+    #[serde(default)]
+    pub inventory: BTreeMap<String, u32>,
+    #[serde(default)]
+    pub pronouns: Pronouns,
+}
+
+/// [`Pronouns`] the texts refer to a character with. Their localized forms
+/// are the messages `pronoun-<id>`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Pronouns {
+    #[default]
+    They,
+    She,
+    He,
+}
+
+impl Pronouns {
+    pub fn id(self) -> &'static str {
+        match self {
+            Pronouns::They => "they",
+            Pronouns::She => "she",
+            Pronouns::He => "he",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HitPoints {
+    pub current: u32,
+    pub max: u32,
+}
+
+impl HitPoints {
+    pub fn is_alive(&self) -> bool {
+        self.current > 0
+    }
+}
+
+impl Character {
+    pub const MAX_LEVEL: u32 = 20;
+    pub const CLASS_POINTS_PER_LEVEL: u32 = 1;
+    const BASE_HIT_POINTS: u32 = 10;
+    const EXPERIENCE_STEP: u32 = 100;
+
+    /// Create a level 1 character that starts at the root of the class tree.
+    pub fn new(name: &str, attributes: Attributes, class_tree: &ClassTree) -> Character {
+        let max = Self::BASE_HIT_POINTS.saturating_add(attributes.constitution);
+        Self {
+            name: name.to_string(),
+            level: 1,
+            experience: 0,
+            attributes,
+            hit_points: HitPoints { current: max, max },
+            class_points: 0,
+            classes: vec![class_tree.root.name.clone()],
+            inventory: BTreeMap::new(),
+            pronouns: Pronouns::default(),
+        }
+    }
+
+    /// Total experience needed to reach `level`: 100 for level 2, 300 for
+    /// level 3, 600 for level 4 and so on.
+    pub fn experience_for_level(level: u32) -> u32 {
+        Self::EXPERIENCE_STEP * level.saturating_sub(1) * level / 2
+    }
+
+    /// Hit points gained on each level up.
+    pub fn hit_points_per_level(&self) -> u32 {
+        5 + self.attributes.constitution / 4
+    }
+
+    /// Add experience and level up as many times as it allows.
+    /// Returns the number of levels gained.
+    pub fn gain_experience(&mut self, experience: u32) -> u32 {
+        self.experience = self.experience.saturating_add(experience);
+        let mut gained = 0;
+        while self.level < Self::MAX_LEVEL
+            && self.experience >= Self::experience_for_level(self.level + 1)
+        {
+            self.level_up();
+            gained += 1;
+        }
+        gained
+    }
+
+    fn level_up(&mut self) {
+        let hit_points = self.hit_points_per_level();
+        self.level += 1;
+        self.class_points += Self::CLASS_POINTS_PER_LEVEL;
+        self.hit_points.max = self.hit_points.max.saturating_add(hit_points);
+        self.hit_points.current = self.hit_points.current.saturating_add(hit_points);
+    }
+
+    pub fn damage(&mut self, amount: u32) {
+        self.hit_points.current = self.hit_points.current.saturating_sub(amount);
+    }
+
+    pub fn heal(&mut self, amount: u32) {
+        self.hit_points.current = self
+            .hit_points
+            .current
+            .saturating_add(amount)
+            .min(self.hit_points.max);
+    }
+
+    pub fn item_count(&self, item: &str) -> u32 {
+        self.inventory.get(item).copied().unwrap_or(0)
+    }
+
+    pub fn grant_item(&mut self, item: &str, count: u32) {
+        if count > 0 {
+            *self.inventory.entry(item.to_string()).or_insert(0) += count;
+        }
+    }
+
+    /// Remove `count` of `item`. Nothing is removed if there are not enough.
+    pub fn take_item(&mut self, item: &str, count: u32) -> bool {
+        let owned = self.item_count(item);
+        if owned < count {
+            return false;
+        }
+        match owned - count {
+            0 => self.inventory.remove(item),
+            left => self.inventory.insert(item.to_string(), left),
+        };
+        true
+    }
+
+    pub fn class_progress(&self) -> ClassProgress<'_> {
+        ClassProgress {
+            level: self.level,
+            attributes: &self.attributes,
+            classes: &self.classes,
+            class_points: self.class_points,
+        }
+    }
+
+    /// Take `class`, paying its cost in class points.
+    pub fn take_class(
+        &mut self,
+        class_tree: &ClassTree,
+        class: &str,
+    ) -> Result<(), Vec<ClassRejection>> {
+        let node = class_tree.check_class(class, &self.class_progress())?;
+        self.class_points -= node.requirements.cost;
+        self.classes.push(node.name.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests_utils::default_class_tree;
+
+    #[test]
+    fn test_new_character() {
+        let tree = default_class_tree();
+        let character = Character::new("Nameless One", Attributes::default(), &tree);
+        assert_eq!(character.level, 1);
+        assert_eq!(character.classes, vec!["Base".to_string()]);
+        assert_eq!(
+            character.hit_points,
+            HitPoints {
+                current: 20,
+                max: 20
+            }
+        );
+    }
+
+    #[test]
+    fn test_level_up() {
+        let tree = default_class_tree();
+        let mut character = Character::new("Nameless One", Attributes::default(), &tree);
+        assert_eq!(character.gain_experience(99), 0);
+        assert_eq!(character.gain_experience(1), 1);
+        assert_eq!(character.level, 2);
+        assert_eq!(character.gain_experience(500), 2);
+        assert_eq!(character.level, 4);
+        assert_eq!(character.class_points, 3);
+        assert_eq!(
+            character.hit_points,
+            HitPoints {
+                current: 41,
+                max: 41
+            }
+        );
+
+        character.gain_experience(u32::MAX);
+        assert_eq!(character.level, Character::MAX_LEVEL);
+
+        // Hit points stop growing at their maximum.
+        let attributes = Attributes {
+            constitution: u32::MAX,
+            ..Default::default()
+        };
+        let mut character = Character::new("Nameless One", attributes, &tree);
+        assert_eq!(character.hit_points.max, u32::MAX);
+        character.gain_experience(u32::MAX);
+        assert_eq!(character.hit_points.max, u32::MAX);
+        assert_eq!(character.hit_points.current, u32::MAX);
+    }
+
+    #[test]
+    fn test_take_class() {
+        let tree = default_class_tree();
+        let attributes = Attributes {
+            strength: 12,
+            ..Default::default()
+        };
+        let mut character = Character::new("Nameless One", attributes, &tree);
+        assert_eq!(
+            character.take_class(&tree, "Fighter"),
+            Err(vec![
+                ClassRejection::LevelTooLow {
+                    required: 2,
+                    current: 1
+                },
+                ClassRejection::NotEnoughPoints {
+                    cost: 1,
+                    available: 0
+                }
+            ])
+        );
+        character.gain_experience(100);
+        assert_eq!(character.take_class(&tree, "Fighter"), Ok(()));
+        assert_eq!(character.class_points, 0);
+        assert_eq!(
+            character.classes,
+            vec!["Base".to_string(), "Fighter".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_inventory() {
+        let tree = default_class_tree();
+        let mut character = Character::new("Nameless One", Attributes::default(), &tree);
+        character.grant_item("apple", 2);
+        assert!(!character.take_item("apple", 3));
+        assert!(character.take_item("apple", 2));
+        assert_eq!(character.item_count("apple"), 0);
+        assert!(character.inventory.is_empty());
+    }
+
+    #[test]
+    fn test_damage_and_heal() {
+        let tree = default_class_tree();
+        let mut character = Character::new("Nameless One", Attributes::default(), &tree);
+        character.damage(15);
+        assert_eq!(character.hit_points.current, 5);
+        character.heal(100);
+        assert_eq!(character.hit_points.current, 20);
+        character.heal(u32::MAX);
+        assert_eq!(character.hit_points.current, 20);
+        character.damage(100);
+        assert!(!character.hit_points.is_alive());
+    }
+}
@@ -1,8 +1,17 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+};
 
-use crate::data::repository::text::TextRepository;
+use serde::{Deserialize, Serialize};
 
-type ClassIdentifier = &'static str;
+use super::{
+    attribute::{Attribute, Attributes},
+    skill::{Skill, SkillIdentifier},
+};
+use crate::data::{repository::text::TextRepository, source::class::ClassDefinition};
+
+pub type ClassIdentifier = String;
 
 /// # Classes
 ///
@@ -14,17 +23,115 @@ type ClassIdentifier = &'static str;
 #[derive(Debug, Clone)]
 pub struct ClassNode {
     pub name: ClassIdentifier,
+    pub requirements: ClassRequirements,
+    /// Skills granted when the class is taken.
+    pub skills: Vec<SkillIdentifier>,
     pub children: Vec<ClassNode>,
 }
 
+/// [`ClassRequirements`] are what a character must fulfill to take a class,
+/// besides having taken its parent class.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct ClassRequirements {
+    /// Minimum character level.
+    pub level: u32,
+    /// Minimum score of each listed attribute.
+    pub attributes: BTreeMap<Attribute, u32>,
+    /// Classes that cannot be held together with this one.
+    pub exclusive: Vec<ClassIdentifier>,
+    /// Class points spent when taking the class.
+    pub cost: u32,
+}
+
+impl Default for ClassRequirements {
+    fn default() -> Self {
+        Self {
+            level: 1,
+            attributes: BTreeMap::new(),
+            exclusive: vec![],
+            cost: 1,
+        }
+    }
+}
+
+/// [`ClassProgress`] is what [`ClassTree`] needs to know about a character to
+/// decide which classes it may take.
+#[derive(Debug, Clone, Copy)]
+pub struct ClassProgress<'a> {
+    pub level: u32,
+    pub attributes: &'a Attributes,
+    /// Classes already taken, in the order they were taken.
+    pub classes: &'a [ClassIdentifier],
+    pub class_points: u32,
+}
+
+/// Why a character cannot take a class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClassRejection {
+    UnknownClass,
+    AlreadyTaken,
+    ParentNotTaken(ClassIdentifier),
+    LevelTooLow {
+        required: u32,
+        current: u32,
+    },
+    AttributeTooLow {
+        attribute: Attribute,
+        required: u32,
+        current: u32,
+    },
+    ExclusiveWith(ClassIdentifier),
+    NotEnoughPoints {
+        cost: u32,
+        available: u32,
+    },
+}
+
+/// [`ClassOption`] is a class a character could take next, with everything
+/// that still stops it. The class is available when nothing does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassOption {
+    pub class: ClassIdentifier,
+    pub rejections: Vec<ClassRejection>,
+}
+
+impl ClassOption {
+    pub fn is_available(&self) -> bool {
+        self.rejections.is_empty()
+    }
+}
+
 impl ClassNode {
+    fn find(&self, name: &str) -> Option<&ClassNode> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter().find_map(|c| c.find(name))
+    }
+
+    fn parent_of(&self, name: &str) -> Option<&ClassNode> {
+        if self.children.iter().any(|c| c.name == name) {
+            return Some(self);
+        }
+        self.children.iter().find_map(|c| c.parent_of(name))
+    }
+
+    /// Every class of this subtree, depth first.
+    fn walk<'a>(&'a self, nodes: &mut Vec<&'a ClassNode>) {
+        nodes.push(self);
+        for c in self.children.iter() {
+            c.walk(nodes);
+        }
+    }
+
     fn get_description(&self, text: &TextRepository) -> HashMap<String, ClassTreeDescription> {
         let mut description = HashMap::new();
         description.insert(
-            self.name.to_string(),
+            self.name.clone(),
             ClassTreeDescription {
-                name: text.get_message(self.name, None),
-                detail: text.get_attr(self.name, "desc", None),
+                name: text.get_message(&self.name, None),
+                detail: text.get_attr(&self.name, "desc", None),
             },
         );
         for c in self.children.iter() {
@@ -34,104 +141,547 @@ impl ClassNode {
     }
 }
 
-fn class_leaf(name: ClassIdentifier) -> ClassNode {
-    ClassNode {
-        name,
-        children: vec![],
+#[derive(Default, Debug, Clone)]
+pub struct ClassTreeDescription {
+    pub name: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClassTree {
+    pub root: ClassNode,
+}
+
+impl ClassTree {
+    /// Build a [`ClassTree`] from the flat class list of a class manifest.
+    ///
+    /// The definitions must have unique names, exactly one root (a class that
+    /// is nobody's child), no unknown children and no cycles. Every problem found
+    /// is reported at once in the returned [`ClassTreeError`].
+    pub fn from_definitions(definitions: &[ClassDefinition]) -> Result<ClassTree, ClassTreeError> {
+        let mut problems = vec![];
+
+        let mut classes: HashMap<&str, &ClassDefinition> = HashMap::new();
+        for definition in definitions {
+            if classes.insert(&definition.name, definition).is_some() {
+                problems.push(MalformedClass::DuplicateName(definition.name.clone()));
+            }
+        }
+
+        let mut parents: HashMap<&str, Vec<&str>> = HashMap::new();
+        for definition in definitions {
+            for other in definition.requires.exclusive.iter() {
+                if !classes.contains_key(other.as_str()) {
+                    problems.push(MalformedClass::UnknownExclusive {
+                        class: definition.name.clone(),
+                        other: other.clone(),
+                    });
+                }
+            }
+            for child in definition.children.iter() {
+                if !classes.contains_key(child.as_str()) {
+                    problems.push(MalformedClass::UnknownChild {
+                        class: definition.name.clone(),
+                        child: child.clone(),
+                    });
+                    continue;
+                }
+                parents.entry(child).or_default().push(&definition.name);
+            }
+        }
+        for definition in definitions {
+            match parents.get(definition.name.as_str()) {
+                Some(p) if p.len() > 1 => problems.push(MalformedClass::MultipleParents {
+                    class: definition.name.clone(),
+                    parents: p.iter().map(|p| p.to_string()).collect(),
+                }),
+                _ => {}
+            }
+        }
+
+        let roots: Vec<&str> = definitions
+            .iter()
+            .map(|d| d.name.as_str())
+            .filter(|name| !parents.contains_key(name))
+            .collect();
+        let root = match roots.as_slice() {
+            [root] => Some(*root),
+            [] => {
+                problems.push(MalformedClass::NoRoot);
+                None
+            }
+            _ => {
+                problems.push(MalformedClass::MultipleRoots(
+                    roots.iter().map(|r| r.to_string()).collect(),
+                ));
+                None
+            }
+        };
+
+        if let Some(root) = root {
+            let mut reachable = HashSet::new();
+            let mut stack = vec![root];
+            while let Some(name) = stack.pop() {
+                if reachable.insert(name) {
+                    stack.extend(
+                        classes[name]
+                            .children
+                            .iter()
+                            .map(|c| c.as_str())
+                            .filter(|c| classes.contains_key(c)),
+                    );
+                }
+            }
+            // With a single root, everything it cannot reach hangs off a cycle.
+            let cycle: Vec<ClassIdentifier> = definitions
+                .iter()
+                .filter(|d| !reachable.contains(d.name.as_str()))
+                .map(|d| d.name.clone())
+                .collect();
+            if !cycle.is_empty() {
+                problems.push(MalformedClass::Cycle(cycle));
+            }
+        }
+
+        match (root, problems.is_empty()) {
+            (Some(root), true) => Ok(ClassTree {
+                root: build_node(root, &classes),
+            }),
+            _ => Err(ClassTreeError { problems }),
+        }
+    }
+
+    pub fn get_descriptions(&self, text: &TextRepository) -> HashMap<String, ClassTreeDescription> {
+        self.root.get_description(text)
+    }
+
+    pub fn find(&self, name: &str) -> Option<&ClassNode> {
+        self.root.find(name)
+    }
+
+    /// The classes a character can take next, that is the root if it has taken
+    /// nothing yet, otherwise every untaken child of a class it has taken.
+    /// Each option lists why it cannot be taken yet, if any.
+    pub fn next_classes(&self, progress: &ClassProgress) -> Vec<ClassOption> {
+        let mut nodes = vec![];
+        self.root.walk(&mut nodes);
+        nodes
+            .into_iter()
+            .filter(|node| !progress.classes.contains(&node.name))
+            .filter(|node| match self.root.parent_of(&node.name) {
+                Some(parent) => progress.classes.contains(&parent.name),
+                None => progress.classes.is_empty(),
+            })
+            .map(|node| ClassOption {
+                class: node.name.clone(),
+                rejections: self.rejections(node, progress),
+            })
+            .collect()
+    }
+
+    /// Every skill granted by the given classes, in the order the classes were
+    /// taken. A skill granted by several classes is only listed once.
+    pub fn skills(&self, classes: &[ClassIdentifier]) -> Vec<Skill> {
+        let mut skills: Vec<Skill> = vec![];
+        for node in classes.iter().filter_map(|c| self.find(c)) {
+            for skill in node.skills.iter() {
+                if !skills.iter().any(|s| &s.id == skill) {
+                    skills.push(Skill {
+                        id: skill.clone(),
+                        class: node.name.clone(),
+                    });
+                }
+            }
+        }
+        skills
+    }
+
+    /// Check whether a character can take `class` right now.
+    pub fn check_class(
+        &self,
+        class: &str,
+        progress: &ClassProgress,
+    ) -> Result<&ClassNode, Vec<ClassRejection>> {
+        let node = self.find(class).ok_or(vec![ClassRejection::UnknownClass])?;
+        if progress.classes.contains(&node.name) {
+            return Err(vec![ClassRejection::AlreadyTaken]);
+        }
+        let mut rejections = vec![];
+        match self.root.parent_of(class) {
+            Some(parent) if !progress.classes.contains(&parent.name) => {
+                rejections.push(ClassRejection::ParentNotTaken(parent.name.clone()))
+            }
+            _ => {}
+        }
+        rejections.extend(self.rejections(node, progress));
+        match rejections.is_empty() {
+            true => Ok(node),
+            false => Err(rejections),
+        }
+    }
+
+    fn rejections(&self, node: &ClassNode, progress: &ClassProgress) -> Vec<ClassRejection> {
+        let requirements = &node.requirements;
+        let mut rejections = vec![];
+        if progress.level < requirements.level {
+            rejections.push(ClassRejection::LevelTooLow {
+                required: requirements.level,
+                current: progress.level,
+            });
+        }
+        for (attribute, required) in requirements.attributes.iter() {
+            let current = progress.attributes.get(*attribute);
+            if current < *required {
+                rejections.push(ClassRejection::AttributeTooLow {
+                    attribute: *attribute,
+                    required: *required,
+                    current,
+                });
+            }
+        }
+        // Exclusion goes both ways, whichever class declares it.
+        for taken in progress.classes.iter() {
+            let declared_by_taken = self
+                .find(taken)
+                .is_some_and(|t| t.requirements.exclusive.contains(&node.name));
+            if requirements.exclusive.contains(taken) || declared_by_taken {
+                rejections.push(ClassRejection::ExclusiveWith(taken.clone()));
+            }
+        }
+        if progress.class_points < requirements.cost {
+            rejections.push(ClassRejection::NotEnoughPoints {
+                cost: requirements.cost,
+                available: progress.class_points,
+            });
+        }
+        rejections
     }
 }
 
-fn default_class_node() -> ClassNode {
+fn build_node(name: &str, classes: &HashMap<&str, &ClassDefinition>) -> ClassNode {
     ClassNode {
-        name: "Base",
-        children: default_main_classes(),
+        name: name.to_string(),
+        requirements: classes[name].requires.clone(),
+        skills: classes[name].skills.clone(),
+        children: classes[name]
+            .children
+            .iter()
+            .map(|c| build_node(c, classes))
+            .collect(),
     }
 }
 
-fn default_main_classes() -> Vec<ClassNode> {
-    vec![
-        default_citizen(),
-        default_fighter(),
-        default_scholar(),
-        default_cleric(),
-    ]
+/// [`ClassTreeError`] lists every malformed class found in a class manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassTreeError {
+    pub problems: Vec<MalformedClass>,
 }
 
-fn default_citizen() -> ClassNode {
-    ClassNode {
-        name: "Citizen",
-        children: vec![
-            class_leaf("Rogue"),
-            class_leaf("Merchant"),
-            class_leaf("Craftsperson"),
-        ],
-    }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MalformedClass {
+    DuplicateName(ClassIdentifier),
+    UnknownChild {
+        class: ClassIdentifier,
+        child: ClassIdentifier,
+    },
+    MultipleParents {
+        class: ClassIdentifier,
+        parents: Vec<ClassIdentifier>,
+    },
+    UnknownExclusive {
+        class: ClassIdentifier,
+        other: ClassIdentifier,
+    },
+    NoRoot,
+    MultipleRoots(Vec<ClassIdentifier>),
+    Cycle(Vec<ClassIdentifier>),
 }
 
-fn default_fighter() -> ClassNode {
-    ClassNode {
-        name: "Fighter",
-        children: vec![
-            class_leaf("Berserker"),
-            class_leaf("Paladin"),
-            class_leaf("Ranger"),
-        ],
+impl fmt::Display for MalformedClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateName(class) => write!(f, "class {} is defined more than once", class),
+            Self::UnknownChild { class, child } => {
+                write!(f, "class {} has an undefined child {}", class, child)
+            }
+            Self::MultipleParents { class, parents } => write!(
+                f,
+                "class {} has more than one parent: {}",
+                class,
+                parents.join(", ")
+            ),
+            Self::UnknownExclusive { class, other } => {
+                write!(f, "class {} excludes an undefined class {}", class, other)
+            }
+            Self::NoRoot => write!(f, "no root class, every class is a child of another"),
+            Self::MultipleRoots(roots) => {
+                write!(f, "more than one root class: {}", roots.join(", "))
+            }
+            Self::Cycle(classes) => write!(
+                f,
+                "classes unreachable from the root, forming a cycle: {}",
+                classes.join(", ")
+            ),
+        }
     }
 }
 
-fn default_scholar() -> ClassNode {
-    ClassNode {
-        name: "Scholar",
-        children: vec![
-            class_leaf("Wizard"),
-            class_leaf("Mechanic"),
-            class_leaf("Alchemist"),
-        ],
+impl fmt::Display for ClassTreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "malformed class tree:")?;
+        for problem in self.problems.iter() {
+            write!(f, "\n  - {}", problem)?;
+        }
+        Ok(())
     }
 }
 
-fn default_cleric() -> ClassNode {
-    ClassNode {
-        name: "Cleric",
-        children: vec![
-            class_leaf("Priest"),
-            class_leaf("Bard"),
-            class_leaf("Gestalt"),
-        ],
+impl std::error::Error for ClassTreeError {}
+
+#[cfg(test)]
+fn definition(name: &str, children: &[&str]) -> ClassDefinition {
+    ClassDefinition {
+        name: name.to_string(),
+        children: children.iter().map(|c| c.to_string()).collect(),
+        requires: ClassRequirements::default(),
+        skills: vec![],
     }
 }
 
 #[test]
 fn test_show_default_classes() {
-    let classes = default_main_classes();
-    println!("{:#?}", classes);
+    use crate::data::source::class::ClassFileDataSource;
+    crate::tests_utils::logging_init();
+    let classes = ClassFileDataSource::new(crate::tests_utils::assets())
+        .get_class_tree()
+        .unwrap();
+    let classes = classes.read();
+    assert_eq!(classes.root.name, "Base");
+    assert_eq!(classes.root.children.len(), 4);
+    println!("{:#?}", classes.root);
 }
 
-#[derive(Default, Debug, Clone)]
-pub struct ClassTreeDescription {
-    pub name: String,
-    pub detail: String,
+#[test]
+fn test_class_messages() {
+    use crate::data::{repository::messages::MessageId, source::class::ClassFileDataSource};
+    let classes = ClassFileDataSource::new(crate::tests_utils::assets())
+        .get_class_tree()
+        .unwrap();
+    let classes = classes.read();
+    let mut nodes = vec![];
+    classes.root.walk(&mut nodes);
+    // Class and skill names are message ids, with the description in `desc`.
+    for id in nodes
+        .iter()
+        .flat_map(|node| std::iter::once(&node.name).chain(node.skills.iter()))
+    {
+        let message = MessageId::from_id(id);
+        assert!(
+            message.is_some_and(|m| m.attributes().contains(&"desc")),
+            "{} has no English name and description",
+            id
+        );
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct ClassTree {
-    pub root: ClassNode,
+#[test]
+fn test_malformed_classes() {
+    let err = ClassTree::from_definitions(&[
+        definition("Base", &["Fighter", "Nobody"]),
+        definition("Fighter", &["Paladin"]),
+        definition("Fighter", &[]),
+        definition("Paladin", &[]),
+        definition("Orphan", &["Paladin"]),
+    ])
+    .unwrap_err();
+    assert_eq!(
+        err.problems,
+        vec![
+            MalformedClass::DuplicateName("Fighter".to_string()),
+            MalformedClass::UnknownChild {
+                class: "Base".to_string(),
+                child: "Nobody".to_string(),
+            },
+            MalformedClass::MultipleParents {
+                class: "Paladin".to_string(),
+                parents: vec!["Fighter".to_string(), "Orphan".to_string()],
+            },
+            MalformedClass::MultipleRoots(vec!["Base".to_string(), "Orphan".to_string()]),
+        ]
+    );
 }
 
-impl Default for ClassTree {
-    fn default() -> Self {
-        Self::new()
-    }
+#[test]
+fn test_class_cycle() {
+    let err = ClassTree::from_definitions(&[
+        definition("Base", &["Fighter"]),
+        definition("Fighter", &[]),
+        definition("Wizard", &["Alchemist"]),
+        definition("Alchemist", &["Wizard"]),
+    ])
+    .unwrap_err();
+    assert_eq!(
+        err.problems,
+        vec![MalformedClass::Cycle(vec![
+            "Wizard".to_string(),
+            "Alchemist".to_string()
+        ])]
+    );
+
+    let err = ClassTree::from_definitions(&[definition("Base", &["Base"])]).unwrap_err();
+    assert_eq!(err.problems, vec![MalformedClass::NoRoot]);
 }
 
-impl ClassTree {
-    pub fn new() -> ClassTree {
-        Self {
-            root: default_class_node(),
-        }
-    }
+#[cfg(test)]
+use crate::tests_utils::default_class_tree;
 
-    pub fn get_descriptions(&self, text: &TextRepository) -> HashMap<String, ClassTreeDescription> {
-        self.root.get_description(text)
-    }
+#[cfg(test)]
+fn available(options: &[ClassOption]) -> Vec<&str> {
+    options
+        .iter()
+        .filter(|o| o.is_available())
+        .map(|o| o.class.as_str())
+        .collect()
+}
+
+#[test]
+fn test_next_classes_of_new_character() {
+    let tree = default_class_tree();
+    let attributes = Attributes::default();
+    let progress = ClassProgress {
+        level: 1,
+        attributes: &attributes,
+        classes: &[],
+        class_points: 0,
+    };
+    assert_eq!(
+        tree.next_classes(&progress),
+        vec![ClassOption {
+            class: "Base".to_string(),
+            rejections: vec![],
+        }]
+    );
+}
+
+#[test]
+fn test_next_classes_level_and_attributes() {
+    let tree = default_class_tree();
+    let attributes = Attributes::default();
+    let classes = vec!["Base".to_string()];
+    let progress = ClassProgress {
+        level: 1,
+        attributes: &attributes,
+        classes: &classes,
+        class_points: 1,
+    };
+    let options = tree.next_classes(&progress);
+    assert_eq!(options.len(), 4);
+    assert!(available(&options).is_empty());
+    assert!(options
+        .iter()
+        .all(|o| o.rejections.contains(&ClassRejection::LevelTooLow {
+            required: 2,
+            current: 1
+        })));
+
+    let attributes = Attributes {
+        strength: 12,
+        ..attributes
+    };
+    let progress = ClassProgress {
+        level: 2,
+        attributes: &attributes,
+        ..progress
+    };
+    let options = tree.next_classes(&progress);
+    assert_eq!(available(&options), vec!["Citizen", "Fighter"]);
+    let scholar = options.iter().find(|o| o.class == "Scholar").unwrap();
+    assert_eq!(
+        scholar.rejections,
+        vec![ClassRejection::AttributeTooLow {
+            attribute: Attribute::Intelligence,
+            required: 12,
+            current: 10
+        }]
+    );
+}
+
+#[test]
+fn test_next_classes_exclusive_and_cost() {
+    let tree = default_class_tree();
+    let attributes = Attributes {
+        strength: 14,
+        dexterity: 13,
+        constitution: 12,
+        charisma: 12,
+        ..Default::default()
+    };
+    let classes: Vec<ClassIdentifier> = ["Base", "Citizen", "Rogue", "Fighter"]
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+    let progress = ClassProgress {
+        level: 5,
+        attributes: &attributes,
+        classes: &classes,
+        class_points: 1,
+    };
+    let options = tree.next_classes(&progress);
+    let paladin = options.iter().find(|o| o.class == "Paladin").unwrap();
+    assert_eq!(
+        paladin.rejections,
+        vec![
+            ClassRejection::ExclusiveWith("Rogue".to_string()),
+            ClassRejection::NotEnoughPoints {
+                cost: 2,
+                available: 1
+            }
+        ]
+    );
+    assert!(!options
+        .iter()
+        .any(|o| o.class == "Rogue" || o.class == "Wizard"));
+
+    assert_eq!(
+        tree.check_class("Wizard", &progress).unwrap_err(),
+        vec![
+            ClassRejection::ParentNotTaken("Scholar".to_string()),
+            ClassRejection::AttributeTooLow {
+                attribute: Attribute::Intelligence,
+                required: 14,
+                current: 10
+            },
+            ClassRejection::NotEnoughPoints {
+                cost: 2,
+                available: 1
+            }
+        ]
+    );
+    assert_eq!(
+        tree.check_class("Rogue", &progress).unwrap_err(),
+        vec![ClassRejection::AlreadyTaken]
+    );
+}
+
+#[test]
+fn test_skills_of_class_path() {
+    let tree = default_class_tree();
+    let classes: Vec<ClassIdentifier> = ["Base", "Scholar", "Wizard"]
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+    let skills = tree.skills(&classes);
+    let skills: Vec<(&str, &str)> = skills
+        .iter()
+        .map(|s| (s.id.as_str(), s.class.as_str()))
+        .collect();
+    assert_eq!(
+        skills,
+        vec![
+            ("Endure", "Base"),
+            ("Study", "Scholar"),
+            ("Fireball", "Wizard"),
+            ("Counterspell", "Wizard"),
+        ]
+    );
 }
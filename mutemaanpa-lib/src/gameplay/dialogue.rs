@@ -0,0 +1,936 @@
+use std::{collections::HashMap, fmt};
+
+use fluent_bundle::FluentArgs;
+use serde::{Deserialize, Serialize};
+
+use crate::data::{repository::text::TextRepository, source::dialogue::DialogueManifest};
+
+use super::{attribute::Attribute, character::Character, class::ClassIdentifier};
+
+pub type NodeIdentifier = String;
+
+/// # Dialogues
+///
+/// A dialogue is a graph of lines. Every [`DialogueNode`] is a line said by a
+/// speaker, followed either by a jump to the next node or by choices for the
+/// player, each jumping somewhere else. A jump to nowhere ends the dialogue.
+///
+/// Lines and speakers are Fluent message ids, resolved through [`TextRepository`]
+/// when displayed, so the graph itself holds no translatable text.
+///
+/// Choices can be guarded by [`Condition`]s on the game state, and both nodes
+/// and choices can fire [`Effect`]s changing it.
+#[derive(Debug, Clone)]
+pub struct DialogueGraph {
+    pub start: NodeIdentifier,
+    pub nodes: HashMap<NodeIdentifier, DialogueNode>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DialogueNode {
+    /// Message id of the speaker's name, none for narration.
+    #[serde(default)]
+    pub speaker: Option<String>,
+    pub line: String,
+    /// Fluent arguments of the line, on top of those given by the game.
+    #[serde(default)]
+    pub args: HashMap<String, DialogueArg>,
+    /// Applied when the dialogue reaches the node.
+    #[serde(default, with = "serde_yaml::with::singleton_map_recursive")]
+    pub effects: Vec<Effect>,
+    #[serde(default)]
+    pub choices: Vec<DialogueChoice>,
+    /// Where to go after the line when there are no choices.
+    #[serde(default)]
+    pub next: Option<NodeIdentifier>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DialogueChoice {
+    pub line: String,
+    #[serde(default)]
+    pub args: HashMap<String, DialogueArg>,
+    /// The choice is only offered when the condition holds.
+    #[serde(default, with = "serde_yaml::with::singleton_map_recursive")]
+    pub condition: Option<Condition>,
+    /// Applied when the player picks the choice, before jumping.
+    #[serde(default, with = "serde_yaml::with::singleton_map_recursive")]
+    pub effects: Vec<Effect>,
+    #[serde(default)]
+    pub jump: Option<NodeIdentifier>,
+}
+
+/// A Fluent argument written in a dialogue graph.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum DialogueArg {
+    Number(f64),
+    String(String),
+}
+
+/// [`Condition`]s guard dialogue choices. They are written as single-key maps,
+/// like `{ class: Merchant }` or `{ not: { flag: met-stranger } }`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    /// The story flag is set.
+    Flag(String),
+    /// The player character has taken the class.
+    Class(ClassIdentifier),
+    /// The player character is at least this level.
+    Level(u32),
+    /// An attribute of the player character is at least `at_least`.
+    Attribute {
+        name: Attribute,
+        at_least: u32,
+    },
+    /// The player character carries at least that many of the item.
+    HasItem(ItemStack),
+    Not(Box<Condition>),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+}
+
+/// [`Effect`]s are how dialogues change the game state.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Effect {
+    SetFlag(String),
+    ClearFlag(String),
+    /// Give items to the player character.
+    GrantItem(ItemStack),
+    /// Take items from the player character, failing if it has too few.
+    TakeItem(ItemStack),
+    GiveExperience(u32),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ItemStack {
+    pub item: String,
+    #[serde(default = "ItemStack::default_count")]
+    pub count: u32,
+}
+
+impl ItemStack {
+    fn default_count() -> u32 {
+        1
+    }
+}
+
+/// [`DialogueContext`] is what a dialogue needs to know about the game, and
+/// what its effects are allowed to change.
+pub trait DialogueContext {
+    fn has_flag(&self, flag: &str) -> bool;
+
+    fn set_flag(&mut self, flag: &str, set: bool);
+
+    /// The player character, none before it is created.
+    fn character(&self) -> Option<&Character>;
+
+    fn character_mut(&mut self) -> Option<&mut Character>;
+
+    /// Fluent arguments every line can use, like `$player`.
+    fn args(&self) -> FluentArgs<'_>;
+}
+
+/// Why a [`Condition`] or an [`Effect`] could not be evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvaluationError {
+    /// It is about the player character, but there is none.
+    NoCharacter,
+    NotEnoughItems {
+        item: String,
+        needed: u32,
+        owned: u32,
+    },
+}
+
+impl fmt::Display for EvaluationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoCharacter => write!(f, "there is no player character"),
+            Self::NotEnoughItems {
+                item,
+                needed,
+                owned,
+            } => write!(f, "needs {} {} but only has {}", needed, item, owned),
+        }
+    }
+}
+
+impl Condition {
+    pub fn check(&self, context: &dyn DialogueContext) -> Result<bool, EvaluationError> {
+        let character = || context.character().ok_or(EvaluationError::NoCharacter);
+        Ok(match self {
+            Condition::Flag(flag) => context.has_flag(flag),
+            Condition::Class(class) => character()?.classes.contains(class),
+            Condition::Level(level) => character()?.level >= *level,
+            Condition::Attribute { name, at_least } => {
+                character()?.attributes.get(*name) >= *at_least
+            }
+            Condition::HasItem(stack) => character()?.item_count(&stack.item) >= stack.count,
+            Condition::Not(condition) => !condition.check(context)?,
+            Condition::All(conditions) => {
+                for condition in conditions {
+                    if !condition.check(context)? {
+                        return Ok(false);
+                    }
+                }
+                true
+            }
+            Condition::Any(conditions) => {
+                for condition in conditions {
+                    if condition.check(context)? {
+                        return Ok(true);
+                    }
+                }
+                false
+            }
+        })
+    }
+}
+
+impl Effect {
+    pub fn apply(&self, context: &mut dyn DialogueContext) -> Result<(), EvaluationError> {
+        match self {
+            Effect::SetFlag(flag) => context.set_flag(flag, true),
+            Effect::ClearFlag(flag) => context.set_flag(flag, false),
+            Effect::GrantItem(stack) => {
+                character_mut(context)?.grant_item(&stack.item, stack.count);
+            }
+            Effect::TakeItem(stack) => {
+                let character = character_mut(context)?;
+                if !character.take_item(&stack.item, stack.count) {
+                    return Err(EvaluationError::NotEnoughItems {
+                        item: stack.item.clone(),
+                        needed: stack.count,
+                        owned: character.item_count(&stack.item),
+                    });
+                }
+            }
+            Effect::GiveExperience(experience) => {
+                character_mut(context)?.gain_experience(*experience);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn character_mut(context: &mut dyn DialogueContext) -> Result<&mut Character, EvaluationError> {
+    context.character_mut().ok_or(EvaluationError::NoCharacter)
+}
+
+impl DialogueGraph {
+    /// Build a [`DialogueGraph`] from a dialogue manifest, checking that the
+    /// start node and every jump exist. All problems are reported at once.
+    pub fn from_manifest(manifest: DialogueManifest) -> Result<DialogueGraph, DialogueGraphError> {
+        let mut problems = vec![];
+        if !manifest.nodes.contains_key(&manifest.start) {
+            problems.push(MalformedDialogue::MissingStart(manifest.start.clone()));
+        }
+        let mut ids: Vec<&NodeIdentifier> = manifest.nodes.keys().collect();
+        ids.sort();
+        for id in ids {
+            let node = &manifest.nodes[id];
+            if node.next.is_some() && !node.choices.is_empty() {
+                problems.push(MalformedDialogue::NextWithChoices(id.clone()));
+            }
+            let jumps = node
+                .choices
+                .iter()
+                .filter_map(|c| c.jump.as_ref())
+                .chain(node.next.iter());
+            for target in jumps {
+                if !manifest.nodes.contains_key(target) {
+                    problems.push(MalformedDialogue::UnknownJump {
+                        node: id.clone(),
+                        target: target.clone(),
+                    });
+                }
+            }
+        }
+        match problems.is_empty() {
+            true => Ok(DialogueGraph {
+                start: manifest.start,
+                nodes: manifest.nodes,
+            }),
+            false => Err(DialogueGraphError { problems }),
+        }
+    }
+}
+
+/// [`DialogueGraphError`] lists every problem found in a dialogue manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialogueGraphError {
+    pub problems: Vec<MalformedDialogue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MalformedDialogue {
+    MissingStart(NodeIdentifier),
+    UnknownJump {
+        node: NodeIdentifier,
+        target: NodeIdentifier,
+    },
+    NextWithChoices(NodeIdentifier),
+}
+
+impl fmt::Display for MalformedDialogue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingStart(start) => write!(f, "start node {} is not defined", start),
+            Self::UnknownJump { node, target } => {
+                write!(f, "node {} jumps to an undefined node {}", node, target)
+            }
+            Self::NextWithChoices(node) => {
+                write!(f, "node {} has both choices and a next node", node)
+            }
+        }
+    }
+}
+
+impl fmt::Display for DialogueGraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "malformed dialogue:")?;
+        for problem in self.problems.iter() {
+            write!(f, "\n  - {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DialogueGraphError {}
+
+/// A line of dialogue, ready to display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialogueLine {
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+/// [`Dialogue`] is a dialogue being played, walking through its graph.
+#[derive(Debug, Clone)]
+pub struct Dialogue {
+    pub id: String,
+    graph: DialogueGraph,
+    current: Option<NodeIdentifier>,
+}
+
+/// Where in a dialogue something went wrong. `choice` counts every choice of
+/// the node as written in the graph, not only those offered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialogueLocation {
+    pub dialogue: String,
+    pub node: NodeIdentifier,
+    pub choice: Option<usize>,
+}
+
+impl fmt::Display for DialogueLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "dialogue {}, node {}", self.dialogue, self.node)?;
+        if let Some(choice) = self.choice {
+            write!(f, ", choice {}", choice)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DialogueError {
+    Finished,
+    NoSuchChoice {
+        choice: usize,
+        available: usize,
+    },
+    /// A condition or an effect failed to evaluate.
+    Evaluation {
+        location: DialogueLocation,
+        error: EvaluationError,
+    },
+}
+
+impl fmt::Display for DialogueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Finished => write!(f, "the dialogue is over"),
+            Self::NoSuchChoice { choice, available } => write!(
+                f,
+                "no choice {}, only {} choices are available",
+                choice, available
+            ),
+            Self::Evaluation { location, error } => write!(f, "in {}: {}", location, error),
+        }
+    }
+}
+
+impl std::error::Error for DialogueError {}
+
+impl Dialogue {
+    /// Start playing `graph`, applying the effects of its first node.
+    pub fn new(
+        id: &str,
+        graph: DialogueGraph,
+        context: &mut dyn DialogueContext,
+    ) -> Result<Dialogue, DialogueError> {
+        let dialogue = Self {
+            id: id.to_string(),
+            current: Some(graph.start.clone()),
+            graph,
+        };
+        let mut staged = StagedEffects::new(&*context);
+        dialogue.enter(dialogue.current.as_ref(), &mut staged)?;
+        staged.into_changes().commit(context);
+        Ok(dialogue)
+    }
+
+    /// Resume a dialogue at `node`, without applying its effects again.
+    /// None if the graph has no such node.
+    pub fn resume(id: &str, graph: DialogueGraph, node: &str) -> Option<Dialogue> {
+        graph.nodes.contains_key(node).then(|| Self {
+            id: id.to_string(),
+            current: Some(node.to_string()),
+            graph,
+        })
+    }
+
+    /// Id of the node being played, none once the dialogue is over.
+    pub fn current_id(&self) -> Option<&NodeIdentifier> {
+        self.current.as_ref()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current.is_none()
+    }
+
+    fn node(&self) -> Option<&DialogueNode> {
+        self.current.as_ref().map(|id| &self.graph.nodes[id])
+    }
+
+    fn location(&self, choice: Option<usize>) -> DialogueLocation {
+        DialogueLocation {
+            dialogue: self.id.clone(),
+            node: self.current.clone().unwrap_or_default(),
+            choice,
+        }
+    }
+
+    fn evaluation_error(&self, choice: Option<usize>, error: EvaluationError) -> DialogueError {
+        DialogueError::Evaluation {
+            location: self.location(choice),
+            error,
+        }
+    }
+
+    pub fn current_node(
+        &self,
+        text: &TextRepository,
+        context: &dyn DialogueContext,
+    ) -> Option<DialogueLine> {
+        let node = self.node()?;
+        Some(DialogueLine {
+            speaker: node.speaker.as_ref().map(|s| text.get_message(s, None)),
+            text: resolve(text, context, &node.line, &node.args),
+        })
+    }
+
+    /// The choices offered to the player, in the order [`Dialogue::choose`]
+    /// expects them. Empty when the line is simply followed by the next one,
+    /// or when no choice can be offered.
+    pub fn choices(
+        &self,
+        text: &TextRepository,
+        context: &dyn DialogueContext,
+    ) -> Result<Vec<String>, DialogueError> {
+        Ok(self
+            .available_choices(context)?
+            .iter()
+            .map(|(_, c)| resolve(text, context, &c.line, &c.args))
+            .collect())
+    }
+
+    /// Choices whose condition holds, along with their index in the node.
+    fn available_choices(
+        &self,
+        context: &dyn DialogueContext,
+    ) -> Result<Vec<(usize, &DialogueChoice)>, DialogueError> {
+        let Some(node) = self.node() else {
+            return Ok(vec![]);
+        };
+        let mut choices = vec![];
+        for (i, choice) in node.choices.iter().enumerate() {
+            let available = match &choice.condition {
+                Some(condition) => condition
+                    .check(context)
+                    .map_err(|e| self.evaluation_error(Some(i), e))?,
+                None => true,
+            };
+            if available {
+                choices.push((i, choice));
+            }
+        }
+        Ok(choices)
+    }
+
+    /// Pick the `choice`-th of [`Dialogue::choices`]. When there are no choices,
+    /// `0` moves on to the next line. A node whose conditions hold for none of
+    /// its choices has no next line, so `0` ends the dialogue.
+    ///
+    /// Effects are applied in order: those of the choice, then those of the
+    /// node it leads to. They are applied all or none: on failure the dialogue
+    /// and the context stay as they were.
+    pub fn choose(
+        &mut self,
+        choice: usize,
+        context: &mut dyn DialogueContext,
+    ) -> Result<(), DialogueError> {
+        let node = self.node().ok_or(DialogueError::Finished)?;
+        let mut staged = StagedEffects::new(&*context);
+        let choices = self.available_choices(&staged)?;
+        let next = match choices.is_empty() {
+            true if choice == 0 => node.next.clone(),
+            true => {
+                return Err(DialogueError::NoSuchChoice {
+                    choice,
+                    available: 1,
+                })
+            }
+            false => {
+                let (i, picked) = choices.get(choice).ok_or(DialogueError::NoSuchChoice {
+                    choice,
+                    available: choices.len(),
+                })?;
+                for effect in picked.effects.iter() {
+                    effect
+                        .apply(&mut staged)
+                        .map_err(|e| self.evaluation_error(Some(*i), e))?;
+                }
+                picked.jump.clone()
+            }
+        };
+        self.enter(next.as_ref(), &mut staged)?;
+        staged.into_changes().commit(context);
+        self.current = next;
+        Ok(())
+    }
+
+    /// Apply the effects of the node `id` to `staged`.
+    fn enter(
+        &self,
+        id: Option<&NodeIdentifier>,
+        staged: &mut StagedEffects,
+    ) -> Result<(), DialogueError> {
+        let Some(id) = id else {
+            return Ok(());
+        };
+        for effect in self.graph.nodes[id].effects.iter() {
+            effect
+                .apply(staged)
+                .map_err(|error| DialogueError::Evaluation {
+                    location: DialogueLocation {
+                        dialogue: self.id.clone(),
+                        node: id.clone(),
+                        choice: None,
+                    },
+                    error,
+                })?;
+        }
+        Ok(())
+    }
+}
+
+/// [`StagedEffects`] records the effects applied over a context without
+/// changing it, so that they can be committed once all of them succeeded.
+struct StagedEffects<'a> {
+    context: &'a dyn DialogueContext,
+    changes: StagedChanges,
+}
+
+/// Changes of [`StagedEffects`], no longer borrowing the context.
+#[derive(Default)]
+struct StagedChanges {
+    /// Flags set or cleared, in order.
+    flags: Vec<(String, bool)>,
+    /// The player character once changed, cloned on the first change.
+    character: Option<Character>,
+}
+
+impl<'a> StagedEffects<'a> {
+    fn new(context: &'a dyn DialogueContext) -> StagedEffects<'a> {
+        StagedEffects {
+            context,
+            changes: StagedChanges::default(),
+        }
+    }
+
+    fn into_changes(self) -> StagedChanges {
+        self.changes
+    }
+}
+
+impl StagedChanges {
+    fn commit(self, context: &mut dyn DialogueContext) {
+        for (flag, set) in self.flags.iter() {
+            context.set_flag(flag, *set);
+        }
+        if let (Some(changed), Some(character)) = (self.character, context.character_mut()) {
+            *character = changed;
+        }
+    }
+}
+
+impl DialogueContext for StagedEffects<'_> {
+    fn has_flag(&self, flag: &str) -> bool {
+        match self.changes.flags.iter().rev().find(|(f, _)| f == flag) {
+            Some((_, set)) => *set,
+            None => self.context.has_flag(flag),
+        }
+    }
+
+    fn set_flag(&mut self, flag: &str, set: bool) {
+        self.changes.flags.push((flag.to_string(), set));
+    }
+
+    fn character(&self) -> Option<&Character> {
+        self.changes
+            .character
+            .as_ref()
+            .or_else(|| self.context.character())
+    }
+
+    fn character_mut(&mut self) -> Option<&mut Character> {
+        if self.changes.character.is_none() {
+            self.changes.character = self.context.character().cloned();
+        }
+        self.changes.character.as_mut()
+    }
+
+    fn args(&self) -> FluentArgs<'_> {
+        self.context.args()
+    }
+}
+
+fn resolve(
+    text: &TextRepository,
+    context: &dyn DialogueContext,
+    line: &str,
+    line_args: &HashMap<String, DialogueArg>,
+) -> String {
+    let mut args = context.args();
+    for (key, value) in line_args.iter() {
+        match value {
+            DialogueArg::Number(n) => args.set(key.clone(), *n),
+            DialogueArg::String(s) => args.set(key.clone(), s.clone()),
+        }
+    }
+    text.get_message(line, Some(&args))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{
+        data::source::{dialogue::DialogueFileDataSource, language::LanguageFileDataSource},
+        gameplay::attribute::Attributes,
+    };
+
+    #[derive(Default)]
+    struct TestContext {
+        flags: HashSet<String>,
+        character: Option<Character>,
+    }
+
+    impl DialogueContext for TestContext {
+        fn has_flag(&self, flag: &str) -> bool {
+            self.flags.contains(flag)
+        }
+
+        fn set_flag(&mut self, flag: &str, set: bool) {
+            match set {
+                true => self.flags.insert(flag.to_string()),
+                false => self.flags.remove(flag),
+            };
+        }
+
+        fn character(&self) -> Option<&Character> {
+            self.character.as_ref()
+        }
+
+        fn character_mut(&mut self) -> Option<&mut Character> {
+            self.character.as_mut()
+        }
+
+        fn args(&self) -> FluentArgs<'_> {
+            let mut args = FluentArgs::new();
+            args.set("player", "Ada");
+            args
+        }
+    }
+
+    impl TestContext {
+        fn with_character(attributes: Attributes, classes: &[&str]) -> TestContext {
+            let tree = crate::tests_utils::default_class_tree();
+            let mut character = Character::new("Ada", attributes, &tree);
+            character
+                .classes
+                .extend(classes.iter().map(|c| c.to_string()));
+            TestContext {
+                character: Some(character),
+                ..Default::default()
+            }
+        }
+
+        fn character(&self) -> &Character {
+            self.character.as_ref().unwrap()
+        }
+    }
+
+    fn load_graph(id: &str) -> DialogueGraph {
+        crate::tests_utils::logging_init();
+        DialogueFileDataSource::new(crate::tests_utils::assets())
+            .get_dialogue(id)
+            .unwrap()
+            .cloned()
+    }
+
+    fn text() -> TextRepository {
+        TextRepository::new(LanguageFileDataSource::new(crate::tests_utils::assets())).unwrap()
+    }
+
+    /// Play `dialogue` from the start, picking `choices` in turn.
+    fn play(
+        id: &str,
+        context: &mut TestContext,
+        choices: &[usize],
+    ) -> Result<Dialogue, DialogueError> {
+        let mut dialogue = Dialogue::new(id, load_graph(id), context)?;
+        for choice in choices {
+            dialogue.choose(*choice, context)?;
+        }
+        Ok(dialogue)
+    }
+
+    #[test]
+    fn test_play_dialogue() {
+        let text = text();
+        let mut context = TestContext::default();
+        let mut dialogue = play("test", &mut context, &[]).unwrap();
+        let line = dialogue.current_node(&text, &context).unwrap();
+        assert_eq!(line.speaker, Some("Stranger".to_string()));
+        assert!(line.text.contains("Ada"));
+        assert!(dialogue.choices(&text, &context).unwrap().is_empty());
+
+        dialogue.choose(0, &mut context).unwrap();
+        assert_eq!(dialogue.current_id().unwrap(), "ask");
+        assert_eq!(
+            dialogue.choices(&text, &context).unwrap(),
+            vec!["I don't think so.".to_string()]
+        );
+        assert_eq!(
+            dialogue.choose(1, &mut context),
+            Err(DialogueError::NoSuchChoice {
+                choice: 1,
+                available: 1
+            })
+        );
+        dialogue.choose(0, &mut context).unwrap();
+        assert_eq!(dialogue.current_id().unwrap(), "farewell");
+        assert!(context.has_flag("met-stranger"));
+        dialogue.choose(0, &mut context).unwrap();
+        assert!(dialogue.is_finished());
+        assert_eq!(
+            dialogue.choose(0, &mut context),
+            Err(DialogueError::Finished)
+        );
+    }
+
+    #[test]
+    fn test_dialogue_conditions() {
+        let text = text();
+        let mut context = TestContext::default();
+        context.set_flag("met-stranger", true);
+        let dialogue = play("test", &mut context, &[0]).unwrap();
+        assert_eq!(dialogue.choices(&text, &context).unwrap().len(), 2);
+        let dialogue = play("test", &mut context, &[0, 0]).unwrap();
+        assert_eq!(dialogue.current_id().unwrap(), "remember");
+        let line = dialogue.current_node(&text, &context).unwrap();
+        assert!(line.text.contains("3"));
+    }
+
+    #[test]
+    fn test_merchant_buy_apple() {
+        let mut context = TestContext::with_character(Attributes::default(), &[]);
+        context.character.as_mut().unwrap().grant_item("coin", 3);
+        // Only buying and leaving are offered.
+        let dialogue = play("merchant", &mut context, &[]).unwrap();
+        assert_eq!(dialogue.choices(&text(), &context).unwrap().len(), 2);
+
+        let dialogue = play("merchant", &mut context, &[0]).unwrap();
+        assert_eq!(dialogue.current_id().unwrap(), "thanks");
+        assert_eq!(context.character().item_count("coin"), 1);
+        assert_eq!(context.character().item_count("apple"), 1);
+        assert_eq!(context.character().experience, 10);
+
+        // Not enough coins left for a second apple.
+        let dialogue = play("merchant", &mut context, &[]).unwrap();
+        assert_eq!(dialogue.choices(&text(), &context).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_merchant_class_and_attribute() {
+        let mut context = TestContext::with_character(Attributes::default(), &["Merchant"]);
+        play("merchant", &mut context, &[0]).unwrap();
+        assert!(context.has_flag("merchant-discount"));
+        assert_eq!(context.character().item_count("apple"), 1);
+        // The discount is given only once.
+        let dialogue = play("merchant", &mut context, &[]).unwrap();
+        assert_eq!(dialogue.choices(&text(), &context).unwrap().len(), 1);
+
+        let attributes = Attributes {
+            strength: 14,
+            ..Default::default()
+        };
+        let mut context = TestContext::with_character(attributes, &[]);
+        let dialogue = play("merchant", &mut context, &[0]).unwrap();
+        assert_eq!(dialogue.current_id().unwrap(), "threatened");
+        assert!(context.has_flag("merchant-hostile"));
+    }
+
+    #[test]
+    fn test_evaluation_errors() {
+        let mut context = TestContext::default();
+        let dialogue = play("merchant", &mut context, &[]).unwrap();
+        assert_eq!(
+            dialogue.choices(&text(), &context),
+            Err(DialogueError::Evaluation {
+                location: DialogueLocation {
+                    dialogue: "merchant".to_string(),
+                    node: "offer".to_string(),
+                    choice: Some(0),
+                },
+                error: EvaluationError::NoCharacter,
+            })
+        );
+
+        let mut context = TestContext::with_character(Attributes::default(), &[]);
+        let effect = Effect::TakeItem(ItemStack {
+            item: "coin".to_string(),
+            count: 2,
+        });
+        assert_eq!(
+            effect.apply(&mut context),
+            Err(EvaluationError::NotEnoughItems {
+                item: "coin".to_string(),
+                needed: 2,
+                owned: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_no_choice_available() {
+        let manifest: DialogueManifest = serde_yaml::from_str(
+            "
+            start: door
+            nodes:
+              door:
+                line: test
+                choices:
+                  - line: test
+                    condition: { has_item: { item: key } }
+                    jump: door
+            ",
+        )
+        .unwrap();
+        let graph = DialogueGraph::from_manifest(manifest).unwrap();
+        let mut context = TestContext::with_character(Attributes::default(), &[]);
+        let mut dialogue = Dialogue::new("door", graph, &mut context).unwrap();
+        assert!(dialogue.choices(&text(), &context).unwrap().is_empty());
+        dialogue.choose(0, &mut context).unwrap();
+        assert!(dialogue.is_finished());
+    }
+
+    #[test]
+    fn test_failed_effects_are_not_applied() {
+        let manifest: DialogueManifest = serde_yaml::from_str(
+            "
+            start: shop
+            nodes:
+              shop:
+                line: test
+                choices:
+                  - line: test
+                    effects: [{ take_item: { item: coin } }, { set_flag: paid }]
+                    jump: receipt
+              receipt:
+                line: test
+                effects: [{ take_item: { item: receipt-paper } }]
+            ",
+        )
+        .unwrap();
+        let graph = DialogueGraph::from_manifest(manifest).unwrap();
+        let mut context = TestContext::with_character(Attributes::default(), &[]);
+        context.character.as_mut().unwrap().grant_item("coin", 1);
+        let mut dialogue = Dialogue::new("shop", graph, &mut context).unwrap();
+        // Retrying does not take the coin twice.
+        for _ in 0..2 {
+            assert!(matches!(
+                dialogue.choose(0, &mut context),
+                Err(DialogueError::Evaluation { location, .. }) if location.node == "receipt"
+            ));
+            assert_eq!(dialogue.current_id().unwrap(), "shop");
+            assert_eq!(context.character().item_count("coin"), 1);
+            assert!(!context.has_flag("paid"));
+        }
+
+        context
+            .character
+            .as_mut()
+            .unwrap()
+            .grant_item("receipt-paper", 1);
+        dialogue.choose(0, &mut context).unwrap();
+        assert_eq!(context.character().item_count("coin"), 0);
+        assert_eq!(context.character().item_count("receipt-paper"), 0);
+        assert!(context.has_flag("paid"));
+    }
+
+    #[test]
+    fn test_malformed_dialogue() {
+        let node = |next: Option<&str>, jump: Option<&str>| DialogueNode {
+            speaker: None,
+            line: "test".to_string(),
+            args: HashMap::new(),
+            effects: vec![],
+            choices: jump
+                .map(|jump| DialogueChoice {
+                    line: "test".to_string(),
+                    args: HashMap::new(),
+                    condition: None,
+                    effects: vec![],
+                    jump: Some(jump.to_string()),
+                })
+                .into_iter()
+                .collect(),
+            next: next.map(|n| n.to_string()),
+        };
+        let manifest = DialogueManifest {
+            start: "begin".to_string(),
+            nodes: HashMap::from([
+                ("a".to_string(), node(Some("b"), Some("nowhere"))),
+                ("b".to_string(), node(None, None)),
+            ]),
+        };
+        assert_eq!(
+            DialogueGraph::from_manifest(manifest).unwrap_err().problems,
+            vec![
+                MalformedDialogue::MissingStart("begin".to_string()),
+                MalformedDialogue::NextWithChoices("a".to_string()),
+                MalformedDialogue::UnknownJump {
+                    node: "a".to_string(),
+                    target: "nowhere".to_string()
+                },
+            ]
+        );
+    }
+}
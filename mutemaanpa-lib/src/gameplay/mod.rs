@@ -1 +1,5 @@
+pub mod attribute;
+pub mod character;
 pub mod class;
+pub mod dialogue;
+pub mod skill;
@@ -0,0 +1,32 @@
+use crate::data::repository::text::TextRepository;
+
+use super::class::ClassIdentifier;
+
+pub type SkillIdentifier = String;
+
+/// # Skills
+///
+/// Skills are abilities a character can use, granted by the classes it has taken.
+/// The identifier of a skill is also the Fluent message id of its name, and the
+/// `desc` attribute of that message describes it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Skill {
+    pub id: SkillIdentifier,
+    /// The class that granted the skill.
+    pub class: ClassIdentifier,
+}
+
+impl Skill {
+    pub fn get_description(&self, text: &TextRepository) -> SkillDescription {
+        SkillDescription {
+            name: text.get_message(&self.id, None),
+            detail: text.get_attr(&self.id, "desc", None),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct SkillDescription {
+    pub name: String,
+    pub detail: String,
+}
@@ -1,18 +1,48 @@
 /// Call once tracing_subscriber::fmt::init() to initialize tracing.
 #[cfg(test)]
 mod tests_utils {
-    use std::sync::Once;
+    use std::sync::{Arc, Once};
+
+    use once_cell::sync::Lazy;
+
+    use crate::{
+        data::source::{
+            assets::{Assets, ASSETS_DIR},
+            class::ClassFileDataSource,
+        },
+        gameplay::class::ClassTree,
+    };
 
     static INIT: Once = Once::new();
 
+    static ASSETS: Lazy<Arc<Assets>> = Lazy::new(|| Assets::open(ASSETS_DIR, &[]).unwrap());
+
     pub fn logging_init() {
         INIT.call_once(|| {
             tracing_subscriber::fmt::init();
         });
     }
+
+    /// The assets of the source tree, shared by the tests.
+    pub fn assets() -> Arc<Assets> {
+        ASSETS.clone()
+    }
+
+    /// The class tree of the source tree.
+    pub fn default_class_tree() -> ClassTree {
+        logging_init();
+        ClassFileDataSource::new(assets())
+            .get_class_tree()
+            .unwrap()
+            .cloned()
+    }
 }
 
 pub mod data;
+mod error;
 pub mod game_state;
 pub mod gameplay;
-mod setting;
+pub mod save;
+pub mod setting;
+
+pub use error::{Error, Result};
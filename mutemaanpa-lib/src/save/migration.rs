@@ -0,0 +1,135 @@
+//! Migrations upgrade old saves one version at a time. They work on the raw
+//! S-expression of a save, before it is read into a [`SaveFile`], so they can
+//! deal with fields that no longer exist.
+//!
+//! A struct is saved as a list of `(field . value)` pairs, which is what
+//! [`rename_field`] and [`set_field`] edit.
+
+use serde_lexpr::Value;
+
+use super::{SaveError, SaveFile};
+
+/// [`Migration`] upgrades a save of version `from` to version `from + 1`.
+/// The version field itself is updated by [`migrate`].
+pub struct Migration {
+    pub from: u32,
+    pub migrate: fn(Value) -> Result<Value, String>,
+}
+
+/// Migrations of the save format, the oldest first.
+pub const MIGRATIONS: &[Migration] = &[];
+
+/// Bring `save` up to [`SaveFile::VERSION`] using `migrations`.
+pub fn migrate(mut save: Value, migrations: &[Migration]) -> Result<Value, SaveError> {
+    let mut version = save
+        .get("version")
+        .and_then(|v| v.as_u64())
+        .and_then(|v| u32::try_from(v).ok())
+        .ok_or(SaveError::MissingVersion)?;
+    if version > SaveFile::VERSION {
+        return Err(SaveError::TooNew {
+            version,
+            supported: SaveFile::VERSION,
+        });
+    }
+    while version < SaveFile::VERSION {
+        let migration = migrations
+            .iter()
+            .find(|m| m.from == version)
+            .ok_or(SaveError::MissingMigration(version))?;
+        save = (migration.migrate)(save).map_err(|reason| SaveError::Migration {
+            from: version,
+            reason,
+        })?;
+        version += 1;
+        save = set_field(save, "version", Value::from(version));
+    }
+    Ok(save)
+}
+
+fn into_fields(value: Value) -> Vec<(String, Value)> {
+    let Value::Cons(fields) = value else {
+        return vec![];
+    };
+    fields
+        .into_vec()
+        .0
+        .into_iter()
+        .filter_map(|field| match field {
+            Value::Cons(pair) => {
+                let (name, value) = pair.into_pair();
+                Some((name.as_name()?.to_string(), value))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn from_fields(fields: Vec<(String, Value)>) -> Value {
+    Value::list(
+        fields
+            .into_iter()
+            .map(|(name, value)| Value::cons(Value::symbol(name), value)),
+    )
+}
+
+pub fn rename_field(value: Value, from: &str, to: &str) -> Value {
+    let fields = into_fields(value)
+        .into_iter()
+        .map(|(name, value)| match name == from {
+            true => (to.to_string(), value),
+            false => (name, value),
+        })
+        .collect();
+    from_fields(fields)
+}
+
+/// Replace field `name`, adding it if it is missing.
+pub fn set_field(value: Value, name: &str, field: Value) -> Value {
+    let mut fields = into_fields(value);
+    match fields.iter_mut().find(|(n, _)| n == name) {
+        Some((_, value)) => *value = field,
+        None => fields.push((name.to_string(), field)),
+    }
+    from_fields(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate() {
+        // A save from before version 1, when `data` was called `gameplay`.
+        let old = serde_lexpr::parse::from_str(
+            "((version . 0) (metadata (timestamp . 0) (play_time . 0) (location)) \
+             (gameplay (character) (flags \"met-stranger\") (dialogue)))",
+        )
+        .unwrap();
+        let migrations = [Migration {
+            from: 0,
+            migrate: |save| Ok(rename_field(save, "gameplay", "data")),
+        }];
+        let save: SaveFile =
+            serde_lexpr::from_value(&migrate(old.clone(), &migrations).unwrap()).unwrap();
+        assert_eq!(save.version, SaveFile::VERSION);
+        assert!(save.data.flags.contains("met-stranger"));
+
+        assert!(matches!(
+            migrate(old.clone(), &[]),
+            Err(SaveError::MissingMigration(0))
+        ));
+        let failing = [Migration {
+            from: 0,
+            migrate: |_| Err("broken".to_string()),
+        }];
+        assert!(matches!(
+            migrate(old, &failing),
+            Err(SaveError::Migration { from: 0, .. })
+        ));
+        assert!(matches!(
+            migrate(Value::Null, &[]),
+            Err(SaveError::MissingVersion)
+        ));
+    }
+}
@@ -0,0 +1,312 @@
+//! # Saves
+//!
+//! A save is the gameplay part of [`GameState`](crate::game_state::GameState):
+//! the character, story flags and where the player is in a dialogue. Settings,
+//! the class tree and texts come from elsewhere and are not saved.
+//!
+//! Saves are written with `serde_lexpr` into numbered slots. Every save file
+//! records the [`SaveFile::VERSION`] it was written with, and older saves are
+//! brought up to date by [`migration`]s before being read.
+
+use std::{
+    collections::BTreeSet,
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::gameplay::{character::Character, dialogue::NodeIdentifier};
+
+pub mod migration;
+
+pub type SlotNumber = u32;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SaveFile {
+    pub version: u32,
+    pub metadata: SaveMetadata,
+    pub data: SaveData,
+}
+
+/// What a save menu shows about a save, without restoring it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SaveMetadata {
+    /// When the save was written, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Total play time, in seconds.
+    pub play_time: u64,
+    /// Where the player was, for now the dialogue being played.
+    pub location: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SaveData {
+    pub character: Option<Character>,
+    pub flags: BTreeSet<String>,
+    pub dialogue: Option<DialogueSave>,
+}
+
+/// A dialogue being played, by graph id and current node.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DialogueSave {
+    pub id: String,
+    pub node: NodeIdentifier,
+}
+
+impl SaveFile {
+    /// Bump it whenever the format changes, and add a migration from the
+    /// previous version to [`migration::MIGRATIONS`].
+    pub const VERSION: u32 = 1;
+
+    pub fn new(data: SaveData, play_time: Duration) -> SaveFile {
+        let location = data.dialogue.as_ref().map(|d| d.id.clone());
+        Self {
+            version: Self::VERSION,
+            metadata: SaveMetadata {
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default(),
+                play_time: play_time.as_secs(),
+                location,
+            },
+            data,
+        }
+    }
+
+    /// Read a save written by any version up to the current one.
+    pub fn read(reader: impl io::Read) -> Result<SaveFile, SaveError> {
+        let value = serde_lexpr::parse::from_reader(reader).map_err(serde_lexpr::Error::from)?;
+        let value = migration::migrate(value, migration::MIGRATIONS)?;
+        Ok(serde_lexpr::from_value(&value)?)
+    }
+
+    pub fn write(&self, writer: impl io::Write) -> Result<(), SaveError> {
+        Ok(serde_lexpr::to_writer(writer, self)?)
+    }
+}
+
+/// [`SaveSlots`] manages the numbered save files of a directory.
+#[derive(Debug, Clone)]
+pub struct SaveSlots {
+    dir: PathBuf,
+}
+
+impl Default for SaveSlots {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_SAVES_DIR)
+    }
+}
+
+impl SaveSlots {
+    pub const DEFAULT_SAVES_DIR: &'static str = "saves";
+
+    pub fn new(dir: impl Into<PathBuf>) -> SaveSlots {
+        Self { dir: dir.into() }
+    }
+
+    pub fn path(&self, slot: SlotNumber) -> PathBuf {
+        self.dir.join(format!("slot-{}.sav", slot))
+    }
+
+    /// Write `save` into `slot`, replacing what was there. The old save is
+    /// kept until the new one is completely written.
+    pub fn save(&self, slot: SlotNumber, save: &SaveFile) -> Result<(), SaveError> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(slot);
+        let temporary = path.with_extension("sav.tmp");
+        let written = Self::write_file(&temporary, save)
+            .and_then(|()| fs::rename(&temporary, &path).map_err(SaveError::from));
+        if let Err(err) = written {
+            let _ = fs::remove_file(&temporary);
+            return Err(err);
+        }
+        info!("saved to slot {}", slot);
+        Ok(())
+    }
+
+    /// Write `save` to `path` and wait until it is on the disk.
+    fn write_file(path: &Path, save: &SaveFile) -> Result<(), SaveError> {
+        let mut writer = io::BufWriter::new(fs::File::create(path)?);
+        save.write(&mut writer)?;
+        writer.flush()?;
+        writer.into_inner().map_err(io::Error::from)?.sync_all()?;
+        Ok(())
+    }
+
+    pub fn load(&self, slot: SlotNumber) -> Result<SaveFile, SaveError> {
+        let file = fs::File::open(self.path(slot)).map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => SaveError::EmptySlot(slot),
+            _ => SaveError::Io(err),
+        })?;
+        SaveFile::read(io::BufReader::new(file))
+    }
+
+    pub fn delete(&self, slot: SlotNumber) -> Result<(), SaveError> {
+        fs::remove_file(self.path(slot)).map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => SaveError::EmptySlot(slot),
+            _ => SaveError::Io(err),
+        })
+    }
+
+    /// Every used slot in order, with its metadata or why it cannot be read.
+    pub fn list(&self) -> Vec<(SlotNumber, Result<SaveMetadata, SaveError>)> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return vec![];
+        };
+        let mut slots: Vec<SlotNumber> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                entry
+                    .file_name()
+                    .to_str()?
+                    .strip_prefix("slot-")?
+                    .strip_suffix(".sav")?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        slots.sort();
+        slots
+            .into_iter()
+            .map(|slot| (slot, self.load(slot).map(|save| save.metadata)))
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    EmptySlot(SlotNumber),
+    Io(io::Error),
+    Format(serde_lexpr::Error),
+    /// The save has no readable `version` field.
+    MissingVersion,
+    /// The save was written by a newer version of the game.
+    TooNew {
+        version: u32,
+        supported: u32,
+    },
+    /// No migration upgrades saves from this version.
+    MissingMigration(u32),
+    Migration {
+        from: u32,
+        reason: String,
+    },
+    /// The dialogue the save was in cannot be resumed.
+    Dialogue {
+        id: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptySlot(slot) => write!(f, "save slot {} is empty", slot),
+            Self::Io(err) => write!(f, "cannot access save: {}", err),
+            Self::Format(err) => write!(f, "malformed save: {}", err),
+            Self::MissingVersion => write!(f, "save has no version"),
+            Self::TooNew { version, supported } => write!(
+                f,
+                "save version {} is newer than the supported version {}",
+                version, supported
+            ),
+            Self::MissingMigration(version) => {
+                write!(f, "cannot upgrade saves from version {}", version)
+            }
+            Self::Migration { from, reason } => {
+                write!(f, "cannot upgrade save from version {}: {}", from, reason)
+            }
+            Self::Dialogue { id, reason } => {
+                write!(f, "cannot resume dialogue {}: {}", id, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SaveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Format(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_lexpr::Error> for SaveError {
+    fn from(err: serde_lexpr::Error) -> Self {
+        Self::Format(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gameplay::attribute::Attributes;
+
+    fn test_save() -> SaveFile {
+        let tree = crate::tests_utils::default_class_tree();
+        let mut character = Character::new("Ada", Attributes::default(), &tree);
+        character.grant_item("apple", 2);
+        SaveFile::new(
+            SaveData {
+                character: Some(character),
+                flags: BTreeSet::from(["met-stranger".to_string()]),
+                dialogue: Some(DialogueSave {
+                    id: "test".to_string(),
+                    node: "ask".to_string(),
+                }),
+            },
+            Duration::from_secs(90),
+        )
+    }
+
+    #[test]
+    fn test_save_slots() {
+        let dir = tempfile::tempdir().unwrap();
+        let slots = SaveSlots::new(dir.path().join("saves"));
+        let save = test_save();
+        assert_eq!(save.metadata.location.as_deref(), Some("test"));
+        slots.save(1, &save).unwrap();
+        slots.save(3, &save).unwrap();
+        assert_eq!(slots.load(1).unwrap(), save);
+        assert!(matches!(slots.load(2), Err(SaveError::EmptySlot(2))));
+
+        let listed: Vec<SlotNumber> = slots.list().into_iter().map(|(slot, _)| slot).collect();
+        assert_eq!(listed, vec![1, 3]);
+
+        // A save that cannot be written leaves the old one in place.
+        let temporary = slots.path(1).with_extension("sav.tmp");
+        assert!(!temporary.exists());
+        fs::create_dir(&temporary).unwrap();
+        assert!(matches!(slots.save(1, &save), Err(SaveError::Io(_))));
+        assert_eq!(slots.load(1).unwrap(), save);
+        fs::remove_dir(&temporary).unwrap();
+
+        slots.delete(1).unwrap();
+        assert!(matches!(slots.delete(1), Err(SaveError::EmptySlot(1))));
+    }
+
+    #[test]
+    fn test_too_new_save() {
+        let mut save = test_save();
+        save.version = SaveFile::VERSION + 1;
+        let mut buffer = vec![];
+        save.write(&mut buffer).unwrap();
+        assert!(matches!(
+            SaveFile::read(buffer.as_slice()),
+            Err(SaveError::TooNew { .. })
+        ));
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+use super::check_range;
+use crate::Result;
+
+/// Volumes are percents of the loudest sound.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct AudioSetting {
+    pub master_volume: u8,
+    /// Relative to the master volume, like the others.
+    pub music_volume: u8,
+    pub sfx_volume: u8,
+}
+
+impl AudioSetting {
+    pub const VOLUMES: std::ops::RangeInclusive<u8> = 0..=100;
+
+    pub fn validate(&self) -> Result<()> {
+        check_range("master volume", self.master_volume, Self::VOLUMES)?;
+        check_range("music volume", self.music_volume, Self::VOLUMES)?;
+        check_range("sfx volume", self.sfx_volume, Self::VOLUMES)
+    }
+}
+
+impl Default for AudioSetting {
+    fn default() -> Self {
+        Self {
+            master_volume: 80,
+            music_volume: 70,
+            sfx_volume: 100,
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Keys are named like `W`, `Enter` or `Escape`, front-ends map the names to
+/// their own key codes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct ControlsSetting {
+    pub key_bindings: BTreeMap<Action, String>,
+}
+
+impl ControlsSetting {
+    /// The key bound to `action`, if any.
+    pub fn key(&self, action: Action) -> Option<&str> {
+        self.key_bindings.get(&action).map(String::as_str)
+    }
+
+    /// Every action has a key, keys cannot be empty, and a key triggers one
+    /// action at most.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(action) = Action::ALL
+            .into_iter()
+            .find(|action| !self.key_bindings.contains_key(action))
+        {
+            return Err(Error::InvalidSetting {
+                name: action.id().to_string(),
+                reason: "no key".to_string(),
+            });
+        }
+        let mut actions: BTreeMap<&str, Action> = BTreeMap::new();
+        for (action, key) in self.key_bindings.iter() {
+            let invalid = |reason: String| Error::InvalidSetting {
+                name: action.id().to_string(),
+                reason,
+            };
+            if key.trim().is_empty() {
+                return Err(invalid("no key".to_string()));
+            }
+            if let Some(other) = actions.insert(key.as_str(), *action) {
+                return Err(invalid(format!("{} is already bound to {}", key, other)));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for ControlsSetting {
+    fn default() -> Self {
+        let key_bindings = Action::ALL
+            .into_iter()
+            .map(|action| (action, action.default_key().to_string()))
+            .collect();
+        Self { key_bindings }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Confirm,
+    Cancel,
+    Menu,
+}
+
+impl Action {
+    pub const ALL: [Action; 7] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Confirm,
+        Action::Cancel,
+        Action::Menu,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Action::MoveUp => "move-up",
+            Action::MoveDown => "move-down",
+            Action::MoveLeft => "move-left",
+            Action::MoveRight => "move-right",
+            Action::Confirm => "confirm",
+            Action::Cancel => "cancel",
+            Action::Menu => "menu",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Action> {
+        Self::ALL.into_iter().find(|a| a.id() == id)
+    }
+
+    fn default_key(self) -> &'static str {
+        match self {
+            Action::MoveUp => "W",
+            Action::MoveDown => "S",
+            Action::MoveLeft => "A",
+            Action::MoveRight => "D",
+            Action::Confirm => "Enter",
+            Action::Cancel => "Escape",
+            Action::Menu => "Tab",
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::check_range;
+use crate::Result;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct GameplaySetting {
+    /// Characters of dialogue shown per second.
+    pub text_speed: u16,
+    pub difficulty: Difficulty,
+}
+
+impl GameplaySetting {
+    pub const TEXT_SPEEDS: std::ops::RangeInclusive<u16> = 10..=200;
+
+    pub fn validate(&self) -> Result<()> {
+        check_range("text speed", self.text_speed, Self::TEXT_SPEEDS)
+    }
+}
+
+impl Default for GameplaySetting {
+    fn default() -> Self {
+        Self {
+            text_speed: 40,
+            difficulty: Difficulty::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    /// For players who are here for the story.
+    Story,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Story, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Difficulty::Story => "story",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Difficulty> {
+        Self::ALL.into_iter().find(|d| d.id() == id)
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
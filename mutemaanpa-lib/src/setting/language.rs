@@ -3,12 +3,26 @@ use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct LanguageSetting {
     pub language: String,
+    /// Languages to try, in order, for texts missing in `language`. English
+    /// always comes last.
+    #[serde(default)]
+    pub fallback: Vec<String>,
+}
+
+impl LanguageSetting {
+    /// `language` followed by its fallbacks.
+    pub fn chain(&self) -> Vec<&str> {
+        std::iter::once(self.language.as_str())
+            .chain(self.fallback.iter().map(|l| l.as_str()))
+            .collect()
+    }
 }
 
 impl Default for LanguageSetting {
     fn default() -> Self {
         Self {
             language: "en".to_string(),
+            fallback: vec![],
         }
     }
 }
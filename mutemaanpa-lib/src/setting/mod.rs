@@ -1,15 +1,60 @@
+use std::{fmt::Display, ops::RangeInclusive};
+
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 
 use self::language::LanguageSetting;
+pub use self::{
+    audio::AudioSetting,
+    controls::{Action, ControlsSetting},
+    gameplay::{Difficulty, GameplaySetting},
+    video::{Resolution, VideoSetting},
+};
+use crate::{Error, Result};
 
+mod audio;
+mod controls;
+mod gameplay;
 mod language;
+mod video;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Setting {
-    pub language: language::LanguageSetting,
+    pub language: LanguageSetting,
+    /// Ids of the enabled mods, see [`crate::data::source::mods`].
+    #[serde(default)]
+    pub mods: Vec<String>,
+    #[serde(default)]
+    pub audio: AudioSetting,
+    #[serde(default)]
+    pub video: VideoSetting,
+    #[serde(default)]
+    pub controls: ControlsSetting,
+    #[serde(default)]
+    pub gameplay: GameplaySetting,
     path: String,
 }
 
+/// [`SettingChange`] is one setting edited by the player, see
+/// [`Setting::change`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingChange {
+    MasterVolume(u8),
+    MusicVolume(u8),
+    SfxVolume(u8),
+    Resolution(Resolution),
+    Fullscreen(bool),
+    Vsync(bool),
+    TextSpeed(u16),
+    Difficulty(Difficulty),
+    /// Bind the key to the action. The action the key was bound to gets the
+    /// previous key of `action`.
+    KeyBinding {
+        action: Action,
+        key: String,
+    },
+}
+
 impl Default for Setting {
     fn default() -> Setting {
         Self::new(Self::DEFAULT_SETTINGS_PATH)
@@ -22,21 +67,124 @@ impl Setting {
     pub fn new(path: &str) -> Setting {
         Self {
             language: LanguageSetting::default(),
+            mods: vec![],
+            audio: AudioSetting::default(),
+            video: VideoSetting::default(),
+            controls: ControlsSetting::default(),
+            gameplay: GameplaySetting::default(),
             path: path.to_string(),
         }
     }
 
-    pub fn load(path: &str) -> Result<Self, anyhow::Error> {
-        let file = std::fs::File::open(path)?;
-        let setting: Self = serde_lexpr::from_reader(file)?;
+    /// Read the settings at `path`. Sections with invalid values get their
+    /// defaults, the file is left as it is.
+    pub fn load(path: &str) -> Result<Self> {
+        let io_error = |source| Error::SettingIo {
+            path: path.to_string(),
+            source,
+        };
+        let file = std::fs::File::open(path).map_err(io_error)?;
+        let mut setting: Self =
+            serde_lexpr::from_reader(file).map_err(|source| Error::SettingParse {
+                path: path.to_string(),
+                source,
+            })?;
+        for problem in setting.repair() {
+            warn!(
+                "{} has an invalid setting, using its default: {}",
+                path, problem
+            );
+        }
         Ok(setting)
     }
 
-    pub fn save(&self) -> Result<(), anyhow::Error> {
-        let file = std::fs::File::create(self.path.as_str())?;
-        serde_lexpr::to_writer(file, self)?;
+    /// Check every value is in its range. Sections missing from the file
+    /// have their defaults.
+    pub fn validate(&self) -> Result<()> {
+        self.audio.validate()?;
+        self.video.validate()?;
+        self.controls.validate()?;
+        self.gameplay.validate()
+    }
+
+    /// Reset the sections with invalid values to their defaults, returning
+    /// what was invalid.
+    pub fn repair(&mut self) -> Vec<Error> {
+        let mut problems = vec![];
+        reset_invalid(&mut self.audio, AudioSetting::validate, &mut problems);
+        reset_invalid(&mut self.video, VideoSetting::validate, &mut problems);
+        reset_invalid(&mut self.controls, ControlsSetting::validate, &mut problems);
+        reset_invalid(&mut self.gameplay, GameplaySetting::validate, &mut problems);
+        problems
+    }
+
+    /// Apply `change` if the settings stay valid. The settings are not saved.
+    pub fn change(&mut self, change: SettingChange) -> Result<()> {
+        let mut changed = self.clone();
+        match change {
+            SettingChange::MasterVolume(volume) => changed.audio.master_volume = volume,
+            SettingChange::MusicVolume(volume) => changed.audio.music_volume = volume,
+            SettingChange::SfxVolume(volume) => changed.audio.sfx_volume = volume,
+            SettingChange::Resolution(resolution) => changed.video.resolution = resolution,
+            SettingChange::Fullscreen(fullscreen) => changed.video.fullscreen = fullscreen,
+            SettingChange::Vsync(vsync) => changed.video.vsync = vsync,
+            SettingChange::TextSpeed(speed) => changed.gameplay.text_speed = speed,
+            SettingChange::Difficulty(difficulty) => changed.gameplay.difficulty = difficulty,
+            SettingChange::KeyBinding { action, key } => {
+                let bindings = &mut changed.controls.key_bindings;
+                let previous = bindings.insert(action, key.clone());
+                let other = bindings
+                    .iter()
+                    .find(|(other, bound)| **other != action && **bound == key)
+                    .map(|(other, _)| *other);
+                if let (Some(other), Some(previous)) = (other, previous) {
+                    bindings.insert(other, previous);
+                }
+            }
+        }
+        changed.validate()?;
+        *self = changed;
         Ok(())
     }
+
+    pub fn save(&self) -> Result<()> {
+        let io_error = |source| Error::SettingIo {
+            path: self.path.clone(),
+            source,
+        };
+        let file = std::fs::File::create(self.path.as_str()).map_err(io_error)?;
+        serde_lexpr::to_writer(file, self).map_err(|source| io_error(source.into()))
+    }
+}
+
+fn reset_invalid<T: Default>(
+    section: &mut T,
+    validate: fn(&T) -> Result<()>,
+    problems: &mut Vec<Error>,
+) {
+    if let Err(err) = validate(section) {
+        problems.push(err);
+        *section = T::default();
+    }
+}
+
+fn check_range<T: PartialOrd + Display>(
+    name: &str,
+    value: T,
+    range: RangeInclusive<T>,
+) -> Result<()> {
+    match range.contains(&value) {
+        true => Ok(()),
+        false => Err(Error::InvalidSetting {
+            name: name.to_string(),
+            reason: format!(
+                "{} is not between {} and {}",
+                value,
+                range.start(),
+                range.end()
+            ),
+        }),
+    }
 }
 
 #[cfg(test)]
@@ -54,4 +202,91 @@ mod tests {
         assert_eq!(setting, loaded_setting);
         fs::remove_file(path).unwrap();
     }
+
+    #[test]
+    fn test_malformed_setting() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.cfg");
+        let path = path.to_str().unwrap();
+        assert!(matches!(Setting::load(path), Err(Error::SettingIo { .. })));
+        fs::write(path, "((language (language . 3)))").unwrap();
+        assert!(matches!(
+            Setting::load(path),
+            Err(Error::SettingParse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_setting_sections() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.cfg");
+        let path = path.to_str().unwrap();
+        // Settings written before the sections existed get their defaults.
+        fs::write(
+            path,
+            "((language (language . \"en\") (fallback)) (path . \"x\"))",
+        )
+        .unwrap();
+        let setting = Setting::load(path).unwrap();
+        assert_eq!(setting.audio, AudioSetting::default());
+        assert_eq!(setting.controls.key(Action::Confirm), Some("Enter"));
+
+        let mut setting = Setting::new(path);
+        setting.change(SettingChange::MusicVolume(30)).unwrap();
+        setting.change(SettingChange::Fullscreen(true)).unwrap();
+        setting
+            .change(SettingChange::Difficulty(Difficulty::Hard))
+            .unwrap();
+        setting
+            .change(SettingChange::KeyBinding {
+                action: Action::Menu,
+                key: "Enter".to_string(),
+            })
+            .unwrap();
+        // The action the key was bound to gets the previous key.
+        assert_eq!(setting.controls.key(Action::Menu), Some("Enter"));
+        assert_eq!(setting.controls.key(Action::Confirm), Some("Tab"));
+        setting.save().unwrap();
+        assert_eq!(Setting::load(path).unwrap(), setting);
+
+        // Invalid changes are rejected and leave the settings as they were.
+        let saved = setting.clone();
+        for change in [
+            SettingChange::MasterVolume(101),
+            SettingChange::Resolution(Resolution {
+                width: 100,
+                height: 720,
+            }),
+            SettingChange::TextSpeed(0),
+            SettingChange::KeyBinding {
+                action: Action::Cancel,
+                key: " ".to_string(),
+            },
+        ] {
+            assert!(matches!(
+                setting.change(change),
+                Err(Error::InvalidSetting { .. })
+            ));
+        }
+        assert_eq!(setting, saved);
+
+        // Invalid sections of the file get their defaults, the others are kept.
+        setting.audio.sfx_volume = 200;
+        setting.controls.key_bindings.remove(&Action::Cancel);
+        setting.save().unwrap();
+        let written = fs::read_to_string(path).unwrap();
+        let loaded = Setting::load(path).unwrap();
+        assert_eq!(loaded.audio, AudioSetting::default());
+        assert_eq!(loaded.controls, ControlsSetting::default());
+        assert_eq!(loaded.video, setting.video);
+        assert_eq!(loaded.gameplay, setting.gameplay);
+        assert_eq!(fs::read_to_string(path).unwrap(), written);
+
+        let mut unbound = ControlsSetting::default();
+        unbound.key_bindings.remove(&Action::Menu);
+        assert!(matches!(
+            unbound.validate(),
+            Err(Error::InvalidSetting { name, .. }) if name == "menu"
+        ));
+    }
 }
@@ -0,0 +1,66 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::check_range;
+use crate::Result;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct VideoSetting {
+    pub resolution: Resolution,
+    pub fullscreen: bool,
+    pub vsync: bool,
+}
+
+impl VideoSetting {
+    pub fn validate(&self) -> Result<()> {
+        check_range(
+            "resolution width",
+            self.resolution.width,
+            Resolution::WIDTHS,
+        )?;
+        check_range(
+            "resolution height",
+            self.resolution.height,
+            Resolution::HEIGHTS,
+        )
+    }
+}
+
+impl Default for VideoSetting {
+    fn default() -> Self {
+        Self {
+            resolution: Resolution::default(),
+            fullscreen: false,
+            vsync: true,
+        }
+    }
+}
+
+/// Size of the window, or of the screen in fullscreen, in pixels.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Resolution {
+    pub const WIDTHS: std::ops::RangeInclusive<u32> = 640..=7680;
+    pub const HEIGHTS: std::ops::RangeInclusive<u32> = 360..=4320;
+}
+
+impl Default for Resolution {
+    fn default() -> Self {
+        Self {
+            width: 1280,
+            height: 720,
+        }
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
@@ -1,23 +1,81 @@
 use std::collections::HashMap;
 use std::io::{stdin, stdout, Write};
+use std::path::PathBuf;
 
 use anyhow::anyhow;
 use anyhow::Result;
 use mutemaanpa_lib::{
-    game_state::GameState, gameplay::class::ClassNode, gameplay::class::ClassTreeDescription,
+    data::{
+        repository::{
+            messages,
+            text::{Isolation, TextRepository},
+        },
+        source::{
+            assets::{self, Assets},
+            language::{LanguageFileDataSource, TextDirection},
+        },
+    },
+    game_state::{Event, GameState},
+    gameplay::attribute::{Attribute, Attributes},
+    gameplay::class::ClassNode,
+    gameplay::class::ClassTreeDescription,
+    gameplay::skill::SkillDescription,
+    setting::{Action, Difficulty, Resolution, SettingChange},
 };
 use tracing::info;
 
 #[derive(Debug, Clone)]
 enum Command {
     ChangeLanguage(String),
+    ListLanguages,
+    Coverage,
     PrintClasses,
+    PrintSkills(Vec<String>),
+    NewCharacter(String, Attributes),
+    PrintCharacter,
+    GainExperience(u32),
+    TakeClass(String),
+    NextClasses,
+    Talk(String),
+    Choose(usize),
+    Save(u32),
+    Load(u32),
+    ListSaves,
+    ListMods,
+    EnableMod(String),
+    DisableMod(String),
+    PrintSettings,
+    ChangeSetting(SettingChange),
 }
 
 fn main() {
     tracing_subscriber::fmt::init();
     info!("Game client settled.");
-    let mut game_state = GameState::default();
+    let started = parse_args()
+        .and_then(|flag| Ok(assets::resolve_root(flag.as_deref())?))
+        .and_then(|root| {
+            info!("assets found in {}", root.display());
+            Ok(GameState::new(root)?)
+        });
+    let mut game_state = match started {
+        Ok(game_state) => game_state,
+        Err(err) => {
+            // The assets may be what is broken, use the texts of the library.
+            let reason = err.to_string();
+            let message = TextRepository::new(LanguageFileDataSource::new(Assets::embedded()))
+                .map(|mut text| {
+                    text.set_isolation(Isolation::WhenBidirectional);
+                    text.format(messages::startup_failed(reason.as_str()))
+                })
+                .unwrap_or_else(|_| format!("Cannot start the game: {}", reason));
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+    // Terminals show isolation marks instead of using them.
+    game_state.text.set_isolation(Isolation::WhenBidirectional);
+    print_diagnostics(&game_state);
+    print_mod_diagnostics(&game_state);
     info!("Starting main game loop");
     loop {
         render(&mut game_state);
@@ -26,6 +84,22 @@ fn main() {
     }
 }
 
+/// The asset folder asked for with `--assets <folder>`.
+fn parse_args() -> Result<Option<PathBuf>> {
+    let mut args = std::env::args().skip(1);
+    let mut assets = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--assets" => {
+                let folder = args.next().ok_or(anyhow!("--assets needs a folder"))?;
+                assets = Some(PathBuf::from(folder));
+            }
+            _ => return Err(anyhow!("Unknown argument: {}", arg)),
+        }
+    }
+    Ok(assets)
+}
+
 // .......................... Render ..........................................
 
 fn render(_: &mut GameState) {
@@ -37,6 +111,95 @@ fn prompt() {
     print!("]=> ");
 }
 
+fn print_languages(game_state: &GameState) {
+    for language in game_state.text.available_languages() {
+        let completeness = match language.completeness {
+            Some(completeness) => format!("{:.0}%", completeness * 100.0),
+            None => "broken".to_string(),
+        };
+        let direction = match language.direction {
+            TextDirection::LeftToRight => "left-to-right",
+            TextDirection::RightToLeft => "right-to-left",
+        };
+        println!(
+            "{}: {} ({}, {})",
+            language.locale, language.name, completeness, direction
+        );
+    }
+}
+
+fn print_diagnostics(game_state: &GameState) {
+    for diagnostic in game_state.text.diagnostics() {
+        println!("warning: {}", diagnostic);
+    }
+    for problem in game_state.fonts.problems() {
+        println!("warning: {}", problem);
+    }
+}
+
+fn print_mod_diagnostics(game_state: &GameState) {
+    let assets = game_state.assets();
+    for problem in assets.mods().problems() {
+        println!("warning: {}", problem);
+    }
+    for conflict in assets.mod_conflicts() {
+        println!("warning: {}", conflict);
+    }
+}
+
+fn print_mods(game_state: &GameState) {
+    let mods = game_state.assets().mods();
+    if mods.installed().is_empty() {
+        println!("No mods installed.");
+    }
+    for m in mods.installed() {
+        let enabled = match mods
+            .enabled()
+            .iter()
+            .any(|e| e.manifest.id == m.manifest.id)
+        {
+            true => "enabled",
+            false => "disabled",
+        };
+        println!(
+            "{}: {} {} ({})",
+            m.manifest.id, m.manifest.name, m.manifest.version, enabled
+        );
+    }
+    print_mod_diagnostics(game_state);
+}
+
+fn print_settings(game_state: &GameState) {
+    let setting = game_state.setting();
+    let audio = &setting.audio;
+    println!("master-volume: {}", audio.master_volume);
+    println!("music-volume: {}", audio.music_volume);
+    println!("sfx-volume: {}", audio.sfx_volume);
+    let video = &setting.video;
+    println!("resolution: {}", video.resolution);
+    println!("fullscreen: {}", video.fullscreen);
+    println!("vsync: {}", video.vsync);
+    println!("text-speed: {}", setting.gameplay.text_speed);
+    println!("difficulty: {}", setting.gameplay.difficulty);
+    for action in Action::ALL {
+        let key = setting.controls.key(action).unwrap_or("(none)");
+        println!("bind {}: {}", action, key);
+    }
+}
+
+fn print_coverage(game_state: &GameState) -> mutemaanpa_lib::Result<()> {
+    for report in game_state.text.coverage_reports()? {
+        match report.is_complete() {
+            true => println!("{}: complete", report.locale),
+            false => println!("{}: {} problems", report.locale, report.problems.len()),
+        }
+        for problem in report.problems.iter() {
+            println!("    {}", problem);
+        }
+    }
+    Ok(())
+}
+
 fn print_class_tree(game_state: &GameState) {
     let (tree, tree_text) = game_state.get_skill_tree();
     fn print_class_node(
@@ -45,13 +208,116 @@ fn print_class_tree(game_state: &GameState) {
         tree_text: &HashMap<String, ClassTreeDescription>,
     ) {
         let indent = " ".repeat(depth * 2);
-        let ClassTreeDescription { name, detail } = tree_text.get(node.name).unwrap();
+        let ClassTreeDescription { name, detail } = tree_text.get(&node.name).unwrap();
         println!("{}{}:\n\t {}", indent, name, detail,);
         for child in &node.children {
             print_class_node(child, depth + 1, tree_text);
         }
     }
-    print_class_node(&tree.root, 0, &tree_text)
+    print_class_node(&tree.root, 0, tree_text)
+}
+
+fn print_skills(game_state: &GameState, classes: &[String]) {
+    let skills = match classes.is_empty() {
+        true => game_state.get_character_skills(),
+        false => game_state.get_skills(classes),
+    };
+    for (skill, SkillDescription { name, detail }) in skills {
+        println!("{} ({}):\n\t {}", name, skill.class, detail);
+    }
+}
+
+fn print_character(game_state: &GameState) {
+    let Some(character) = game_state.character() else {
+        println!("No character yet, create one with: new-character <name>");
+        return;
+    };
+    println!(
+        "{}, level {} ({} exp), {}/{} HP, {} class points",
+        character.name,
+        character.level,
+        character.experience,
+        character.hit_points.current,
+        character.hit_points.max,
+        character.class_points,
+    );
+    let text = &game_state.text;
+    println!(
+        "\t{}",
+        text.format(messages::character_pronouns(character.pronouns.id()))
+    );
+    for attribute in Attribute::ALL {
+        let value = character.attributes.get(attribute);
+        println!(
+            "\t{}",
+            text.format(messages::character_stat(attribute.id(), value))
+        );
+    }
+    if let Some(class) = character.classes.last() {
+        println!(
+            "\t{}",
+            text.format(messages::character_class(class.as_str()))
+        );
+    }
+    println!("\tclasses: {}", character.classes.join(" > "));
+    for (item, count) in character.inventory.iter() {
+        println!("\t{} x{}", item, count);
+    }
+}
+
+fn print_next_classes(game_state: &GameState) {
+    for option in game_state.next_classes() {
+        match option.is_available() {
+            true => println!("{}: available", option.class),
+            false => println!("{}: {:?}", option.class, option.rejections),
+        }
+    }
+}
+
+fn print_dialogue(game_state: &GameState) {
+    let Some(line) = game_state.dialogue_line() else {
+        return;
+    };
+    match line.speaker {
+        Some(speaker) => println!("{}: {}", speaker, line.text),
+        None => println!("{}", line.text),
+    }
+    let choices = match game_state.dialogue_choices() {
+        Ok(choices) => choices,
+        Err(err) => {
+            println!("{}", err);
+            return;
+        }
+    };
+    match choices.is_empty() {
+        true => println!("\t0. ..."),
+        false => {
+            for (i, choice) in choices.iter().enumerate() {
+                println!("\t{}. {}", i, choice);
+            }
+        }
+    }
+}
+
+fn print_saves(game_state: &GameState) {
+    let saves = game_state.list_saves();
+    if saves.is_empty() {
+        println!("No saves yet, save with: save <slot>");
+    }
+    for (slot, metadata) in saves {
+        match metadata {
+            Ok(metadata) => println!(
+                "{}: saved at {}, {}, in {}",
+                slot,
+                metadata.timestamp,
+                game_state
+                    .text
+                    .format(messages::save_play_time(metadata.play_time)),
+                metadata.location.as_deref().unwrap_or("-"),
+            ),
+            Err(err) => println!("{}: {}", slot, err),
+        }
+    }
 }
 
 // ................................. Control ..................................
@@ -85,7 +351,79 @@ fn parse_user_input(s: String) -> Result<Command> {
                 .ok_or(anyhow!("Change Language needs an argument: <language>"))?
                 .to_string(),
         )),
+        Some("list-languages") => Ok(Command::ListLanguages),
+        Some("coverage") => Ok(Command::Coverage),
         Some("print-classes") => Ok(Command::PrintClasses),
+        Some("print-skills") => Ok(Command::PrintSkills(
+            tokens.map(|class| class.to_string()).collect(),
+        )),
+        Some("new-character") => {
+            let name = tokens
+                .next()
+                .ok_or(anyhow!(
+                    "New character needs an argument: <name> [attributes]"
+                ))?
+                .to_string();
+            let mut attributes = Attributes::default();
+            for (attribute, value) in Attribute::ALL.into_iter().zip(tokens) {
+                attributes.set(attribute, value.parse()?);
+            }
+            Ok(Command::NewCharacter(name, attributes))
+        }
+        Some("print-character") => Ok(Command::PrintCharacter),
+        Some("gain-experience") => Ok(Command::GainExperience(
+            tokens
+                .next()
+                .ok_or(anyhow!("Gain experience needs an argument: <experience>"))?
+                .parse()?,
+        )),
+        Some("take-class") => Ok(Command::TakeClass(
+            tokens
+                .next()
+                .ok_or(anyhow!("Take class needs an argument: <class>"))?
+                .to_string(),
+        )),
+        Some("next-classes") => Ok(Command::NextClasses),
+        Some("talk") => Ok(Command::Talk(
+            tokens
+                .next()
+                .ok_or(anyhow!("Talk needs an argument: <dialogue>"))?
+                .to_string(),
+        )),
+        Some("choose") => Ok(Command::Choose(
+            tokens
+                .next()
+                .ok_or(anyhow!("Choose needs an argument: <choice>"))?
+                .parse()?,
+        )),
+        Some("save") => Ok(Command::Save(
+            tokens
+                .next()
+                .ok_or(anyhow!("Save needs an argument: <slot>"))?
+                .parse()?,
+        )),
+        Some("load") => Ok(Command::Load(
+            tokens
+                .next()
+                .ok_or(anyhow!("Load needs an argument: <slot>"))?
+                .parse()?,
+        )),
+        Some("list-saves") => Ok(Command::ListSaves),
+        Some("list-mods") => Ok(Command::ListMods),
+        Some("enable-mod") => Ok(Command::EnableMod(
+            tokens
+                .next()
+                .ok_or(anyhow!("Enable mod needs an argument: <mod>"))?
+                .to_string(),
+        )),
+        Some("disable-mod") => Ok(Command::DisableMod(
+            tokens
+                .next()
+                .ok_or(anyhow!("Disable mod needs an argument: <mod>"))?
+                .to_string(),
+        )),
+        Some("settings") => Ok(Command::PrintSettings),
+        Some("set") => Ok(Command::ChangeSetting(parse_setting(tokens)?)),
         Some(cmd) => {
             info!("Unrecognized user input: {}", cmd);
             Err(anyhow!("Unrecognized user input: {}", cmd))
@@ -97,15 +435,138 @@ fn parse_user_input(s: String) -> Result<Command> {
     }
 }
 
+/// Parse `<setting> <value>`, or `bind <action> <key>`.
+fn parse_setting<'a>(mut tokens: impl Iterator<Item = &'a str>) -> Result<SettingChange> {
+    let usage = "Set needs arguments: <setting> <value>";
+    let name = tokens.next().ok_or(anyhow!(usage))?;
+    let value = tokens.next().ok_or(anyhow!(usage))?;
+    match name {
+        "master-volume" => Ok(SettingChange::MasterVolume(value.parse()?)),
+        "music-volume" => Ok(SettingChange::MusicVolume(value.parse()?)),
+        "sfx-volume" => Ok(SettingChange::SfxVolume(value.parse()?)),
+        "resolution" => {
+            let (width, height) = value
+                .split_once('x')
+                .ok_or(anyhow!("Resolution looks like 1280x720"))?;
+            Ok(SettingChange::Resolution(Resolution {
+                width: width.parse()?,
+                height: height.parse()?,
+            }))
+        }
+        "fullscreen" => Ok(SettingChange::Fullscreen(value.parse()?)),
+        "vsync" => Ok(SettingChange::Vsync(value.parse()?)),
+        "text-speed" => Ok(SettingChange::TextSpeed(value.parse()?)),
+        "difficulty" => Ok(SettingChange::Difficulty(
+            Difficulty::from_id(value).ok_or(anyhow!("Unknown difficulty: {}", value))?,
+        )),
+        "bind" => Ok(SettingChange::KeyBinding {
+            action: Action::from_id(value).ok_or(anyhow!("Unknown action: {}", value))?,
+            key: tokens
+                .next()
+                .ok_or(anyhow!("Bind needs arguments: <action> <key>"))?
+                .to_string(),
+        }),
+        _ => Err(anyhow!("Unknown setting: {}", name)),
+    }
+}
+
 fn execute_cmd(cmd: Command, game_state: &mut GameState) {
-    match cmd {
-        Command::ChangeLanguage(lang) => game_state.command_handler(
-            mutemaanpa_lib::game_state::Command::ChangeLanguage(lang.parse().unwrap()),
-        ),
-        Command::PrintClasses => print_class_tree(game_state),
+    use mutemaanpa_lib::game_state::Command as GameCommand;
+    let result = match cmd {
+        Command::ChangeLanguage(lang) => {
+            game_state.command_handler(GameCommand::ChangeLanguage(lang))
+        }
+        Command::ListLanguages => {
+            print_languages(game_state);
+            Ok(())
+        }
+        Command::Coverage => print_coverage(game_state),
+        Command::PrintClasses => {
+            print_class_tree(game_state);
+            Ok(())
+        }
+        Command::PrintSkills(classes) => {
+            print_skills(game_state, &classes);
+            Ok(())
+        }
+        Command::NewCharacter(name, attributes) => {
+            game_state.command_handler(GameCommand::CreateCharacter { name, attributes })
+        }
+        Command::PrintCharacter => {
+            print_character(game_state);
+            Ok(())
+        }
+        Command::GainExperience(experience) => {
+            game_state.command_handler(GameCommand::GainExperience(experience))
+        }
+        Command::TakeClass(class) => game_state.command_handler(GameCommand::TakeClass(class)),
+        Command::NextClasses => {
+            print_next_classes(game_state);
+            Ok(())
+        }
+        Command::Talk(dialogue) => game_state.command_handler(GameCommand::StartDialogue(dialogue)),
+        Command::Choose(choice) => game_state.command_handler(GameCommand::ChooseDialogue(choice)),
+        Command::Save(slot) => game_state.command_handler(GameCommand::Save(slot)),
+        Command::Load(slot) => game_state.command_handler(GameCommand::Load(slot)),
+        Command::ListSaves => {
+            print_saves(game_state);
+            Ok(())
+        }
+        Command::ListMods => {
+            print_mods(game_state);
+            Ok(())
+        }
+        Command::EnableMod(id) => game_state
+            .command_handler(GameCommand::EnableMod(id))
+            .map(|()| println!("Restart the game to apply.")),
+        Command::DisableMod(id) => game_state
+            .command_handler(GameCommand::DisableMod(id))
+            .map(|()| println!("Restart the game to apply.")),
+        Command::PrintSettings => {
+            print_settings(game_state);
+            Ok(())
+        }
+        Command::ChangeSetting(change) => {
+            game_state.command_handler(GameCommand::ChangeSetting(change))
+        }
+    };
+    if let Err(err) = result {
+        println!("{}", err);
     }
 }
 
 // ................................. Update ...................................
 
-fn update(_: &mut GameState) {}
+fn update(game_state: &mut GameState) {
+    for event in game_state.update() {
+        info!("received game event: {:?}", event);
+        match event {
+            Event::LanguageChanged => {
+                println!("Language changed.");
+                print_diagnostics(game_state);
+            }
+            Event::SettingChanged => println!("Setting changed."),
+            Event::SettingSaveFailed { reason } => {
+                println!(
+                    "Settings not saved, the change is for this session only: {}",
+                    reason
+                )
+            }
+            Event::ClassTreeChanged => print_class_tree(game_state),
+            Event::CharacterChanged => {}
+            Event::LevelUp { level } => println!("Level up! Now level {}.", level),
+            Event::ClassRejected { class, rejections } => {
+                println!("Cannot take {}: {:?}", class, rejections)
+            }
+            Event::DialogueChanged => print_dialogue(game_state),
+            Event::DialogueEnded => println!("(end of dialogue)"),
+            Event::Saved { slot } => println!("Saved to slot {}.", slot),
+            Event::Loaded { slot } => {
+                println!("Loaded slot {}.", slot);
+                print_character(game_state);
+                print_dialogue(game_state);
+            }
+            Event::SaveFailed { slot, reason } => println!("Slot {}: {}", slot, reason),
+        }
+    }
+}